@@ -0,0 +1,357 @@
+// Headless front end for scripting: runs the same commands as the app without starting Tauri
+
+use app_lib::commands;
+use app_lib::{ExportBundle, McpServer};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Write};
+
+const USAGE: &str = "Usage: mcp-manager-cli [--json] <command> [options]
+
+Commands:
+  list [tool]                              List servers, for every tool or a single one
+  add <tool> <name> -- <command> [args...] Add or replace a stdio server
+  add <tool> <name> --url <url>            Add or replace a remote server
+      [--type <type>] [--header 'Name: value']... [--env KEY=VALUE]... [--disabled]
+  rm <tool> <name>                         Remove a server
+  toggle <tool> <name> [--on | --off]      Enable or disable a server
+  sync <from> <to> [name...]               Copy servers from one tool to another
+  backup [--label <label>] [--note <note>] Create a backup
+  backup --list                            List backups
+  restore <backup> [--tool <tool>]... [--raw]
+                                           Restore a backup
+  export [--output <file>]                 Write all configs as JSON (stdout by default)
+  import <file | -> [--replace]            Import an export, merging unless --replace is given
+
+Options:
+  --json                     Print results as JSON
+  --passphrase-env <VAR>     Read the passphrase for encrypted backups and exports from VAR";
+
+struct Cli {
+    json: bool,
+    passphrase_env: Option<String>,
+    args: Vec<String>,
+}
+
+impl Cli {
+    // Takes `--flag value` out of the remaining args
+    fn value(&mut self, flag: &str) -> Result<Option<String>, String> {
+        match self.args.iter().position(|a| a == flag) {
+            Some(idx) if idx + 1 < self.args.len() => {
+                let value = self.args.remove(idx + 1);
+                self.args.remove(idx);
+                Ok(Some(value))
+            }
+            Some(_) => Err(format!("{} needs a value", flag)),
+            None => Ok(None),
+        }
+    }
+
+    fn values(&mut self, flag: &str) -> Result<Vec<String>, String> {
+        let mut values = vec![];
+        while let Some(value) = self.value(flag)? {
+            values.push(value);
+        }
+        Ok(values)
+    }
+
+    fn flag(&mut self, flag: &str) -> bool {
+        match self.args.iter().position(|a| a == flag) {
+            Some(idx) => {
+                self.args.remove(idx);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Positional args, after all options have been taken out
+    fn positional(&self, count: usize) -> Result<Vec<String>, String> {
+        if let Some(unknown) = self.args.iter().find(|a| a.starts_with("--")) {
+            return Err(format!("Unknown option: {}", unknown));
+        }
+        if self.args.len() != count {
+            return Err(format!("Expected {} argument(s), got {}", count, self.args.len()));
+        }
+        Ok(self.args.clone())
+    }
+
+    fn passphrase(&self) -> Result<Option<String>, String> {
+        match self.passphrase_env {
+            Some(ref var) => std::env::var(var)
+                .map(Some)
+                .map_err(|_| format!("Environment variable {} is not set", var)),
+            None => Ok(None),
+        }
+    }
+
+    fn print<T: Serialize>(&self, value: &T, text: impl FnOnce(&T) -> String) -> Result<(), String> {
+        let output = match self.json {
+            true => serde_json::to_string_pretty(value).map_err(|e| e.to_string())?,
+            false => text(value),
+        };
+        if !output.is_empty() {
+            write_stdout(&output);
+        }
+        Ok(())
+    }
+}
+
+// A closed pipe (`| head`) is not an error worth a panic
+fn write_stdout(output: &str) {
+    let _ = writeln!(std::io::stdout(), "{}", output);
+}
+
+fn describe_server(server: &McpServer) -> String {
+    let target = if server.command.is_empty() {
+        server.url.clone()
+    } else {
+        std::iter::once(&server.command).chain(&server.args).cloned().collect::<Vec<_>>().join(" ")
+    };
+    format!("  [{}] {} ({}) {}", if server.enabled { "on" } else { "off" }, server.name, server.server_type, target)
+}
+
+fn find_server(tool: &str, name: &str) -> Result<McpServer, String> {
+    commands::get_configs(tool.to_string()).into_iter()
+        .find(|s| s.name == name)
+        .ok_or_else(|| format!("Server not found: {}", name))
+}
+
+fn list(cli: Cli) -> Result<(), String> {
+    let tools = match cli.args.len() {
+        0 => commands::get_tools().into_iter().map(|t| t.name).collect(),
+        _ => cli.positional(1)?,
+    };
+    let configs: HashMap<String, Vec<McpServer>> = tools.iter()
+        .map(|tool| (tool.clone(), commands::get_configs(tool.clone())))
+        .collect();
+
+    cli.print(&configs, |configs| {
+        tools.iter()
+            .filter(|tool| !configs[*tool].is_empty() || tools.len() == 1)
+            .map(|tool| {
+                let servers = &configs[tool];
+                let mut lines = vec![format!("{} ({})", tool, servers.len())];
+                lines.extend(servers.iter().map(describe_server));
+                lines.join("\n")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    })
+}
+
+fn add(mut cli: Cli) -> Result<(), String> {
+    let command = match cli.args.iter().position(|a| a == "--") {
+        Some(idx) => {
+            let command: Vec<String> = cli.args.drain(idx..).skip(1).collect();
+            if command.is_empty() {
+                return Err("Missing command after --".to_string());
+            }
+            command
+        }
+        None => vec![],
+    };
+    let url = cli.value("--url")?.unwrap_or_default();
+    let server_type = cli.value("--type")?;
+    let disabled = cli.flag("--disabled");
+    let env = cli.values("--env")?.into_iter()
+        .map(|pair| match pair.split_once('=') {
+            Some((key, value)) => Ok((key.to_string(), value.to_string())),
+            None => Err(format!("Expected KEY=VALUE, got {}", pair)),
+        })
+        .collect::<Result<HashMap<_, _>, _>>()?;
+    let headers = cli.values("--header")?.into_iter()
+        .map(|header| match header.split_once(':') {
+            Some((name, value)) => Ok((name.trim().to_string(), value.trim().to_string())),
+            None => Err(format!("Expected 'Name: value', got {}", header)),
+        })
+        .collect::<Result<HashMap<_, _>, _>>()?;
+    let [tool, name]: [String; 2] = cli.positional(2)?.try_into().unwrap();
+
+    if command.is_empty() == url.is_empty() {
+        return Err("Give either a command after -- or --url".to_string());
+    }
+
+    let server = McpServer {
+        name,
+        server_type: server_type.unwrap_or_else(|| if url.is_empty() { "stdio" } else { "http" }.to_string()),
+        command: command.first().cloned().unwrap_or_default(),
+        args: command.iter().skip(1).cloned().collect(),
+        env,
+        url,
+        headers,
+        enabled: !disabled,
+    };
+    let servers = commands::add_or_update_server(tool.clone(), server.clone())?;
+    cli.print(&servers, |_| format!("Saved {} in {}", server.name, tool))
+}
+
+fn rm(cli: Cli) -> Result<(), String> {
+    let [tool, name]: [String; 2] = cli.positional(2)?.try_into().unwrap();
+    find_server(&tool, &name)?;
+    let servers = commands::delete_server(tool.clone(), name.clone())?;
+    cli.print(&servers, |_| format!("Removed {} from {}", name, tool))
+}
+
+fn toggle(mut cli: Cli) -> Result<(), String> {
+    let wanted = match (cli.flag("--on"), cli.flag("--off")) {
+        (true, true) => return Err("Give only one of --on and --off".to_string()),
+        (true, false) => Some(true),
+        (false, true) => Some(false),
+        (false, false) => None,
+    };
+    let [tool, name]: [String; 2] = cli.positional(2)?.try_into().unwrap();
+
+    let current = find_server(&tool, &name)?;
+    let server = match wanted {
+        Some(enabled) if enabled == current.enabled => current,
+        _ => commands::toggle_server(tool.clone(), name)?,
+    };
+    cli.print(&server, |s| {
+        format!("{} is {} in {}", s.name, if s.enabled { "enabled" } else { "disabled" }, tool)
+    })
+}
+
+fn sync(cli: Cli) -> Result<(), String> {
+    let mut args = cli.positional(cli.args.len().max(2))?;
+    let names: Vec<String> = args.drain(2..).collect();
+    let (from, to) = (args[0].clone(), args[1].clone());
+
+    let count = commands::sync_configs(from.clone(), to.clone(), Some(names).filter(|n| !n.is_empty()))?;
+    cli.print(&serde_json::json!({ "synced": count }), |_| {
+        format!("Synced {} server(s) from {} to {}", count, from, to)
+    })
+}
+
+fn backup(mut cli: Cli) -> Result<(), String> {
+    if cli.flag("--list") {
+        cli.positional(0)?;
+        return cli.print(&commands::get_backups(), |backups| {
+            backups.iter()
+                .map(|b| {
+                    let mut line = format!("{}  {}", b.name, b.trigger);
+                    if let Some(ref label) = b.label {
+                        line.push_str(&format!("  {}", label));
+                    }
+                    if b.encrypted {
+                        line.push_str("  (encrypted)");
+                    }
+                    line
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        });
+    }
+
+    let label = cli.value("--label")?;
+    let note = cli.value("--note")?;
+    cli.positional(0)?;
+    let backup = commands::create_backup(label, note, cli.passphrase()?)?;
+    cli.print(&backup, |b| format!("Created {}", b.name))
+}
+
+fn restore(mut cli: Cli) -> Result<(), String> {
+    let tools = cli.values("--tool")?;
+    let mode = cli.flag("--raw").then(|| "raw".to_string());
+    let [filename]: [String; 1] = cli.positional(1)?.try_into().unwrap();
+
+    let restored = commands::restore_backup(
+        filename,
+        Some(tools).filter(|t| !t.is_empty()),
+        mode,
+        cli.passphrase()?,
+    )?;
+    cli.print(&restored, |tools| format!("Restored {}", tools.join(", ")))
+}
+
+fn export(mut cli: Cli) -> Result<(), String> {
+    let output = cli.value("--output")?;
+    cli.positional(0)?;
+
+    let bundle = commands::export_configs(cli.passphrase()?)?;
+    let json = serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?;
+    match output {
+        Some(path) => {
+            fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+            if !cli.json {
+                eprintln!("Exported to {}", path);
+            }
+        }
+        None => write_stdout(&json),
+    }
+    Ok(())
+}
+
+fn import(mut cli: Cli) -> Result<(), String> {
+    let replace = cli.flag("--replace");
+    let [path]: [String; 1] = cli.positional(1)?.try_into().unwrap();
+
+    let content = if path == "-" {
+        let mut content = String::new();
+        std::io::stdin().read_to_string(&mut content).map_err(|e| e.to_string())?;
+        content
+    } else {
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?
+    };
+    let bundle: ExportBundle = serde_json::from_str(&content).map_err(|e| format!("Not an export: {}", e))?;
+    let (tools, encrypted) = match bundle {
+        ExportBundle::Plain(data) => (Some(data.tools), None),
+        ExportBundle::Encrypted(bundle) => (None, Some(bundle)),
+    };
+
+    let imported = commands::import_configs(tools, encrypted, cli.passphrase()?, !replace)?;
+    cli.print(&imported, |tools| format!("Imported {}", tools.join(", ")))
+}
+
+fn main() {
+    let mut cli = Cli {
+        json: false,
+        passphrase_env: None,
+        args: std::env::args().skip(1).collect(),
+    };
+
+    // Global options can go anywhere before `--`
+    let before_separator = |args: &[String], flag: &str| {
+        args.iter().take_while(|a| *a != "--").position(|a| a == flag)
+    };
+    if let Some(idx) = before_separator(&cli.args, "--json") {
+        cli.args.remove(idx);
+        cli.json = true;
+    }
+    if let Some(idx) = before_separator(&cli.args, "--passphrase-env") {
+        if idx + 1 >= cli.args.len() {
+            eprintln!("error: --passphrase-env needs a value");
+            std::process::exit(2);
+        }
+        cli.passphrase_env = Some(cli.args.remove(idx + 1));
+        cli.args.remove(idx);
+    }
+
+    if cli.args.is_empty() || cli.args[0] == "--help" || cli.args[0] == "-h" || cli.args[0] == "help" {
+        write_stdout(USAGE);
+        return;
+    }
+    let command = cli.args.remove(0);
+
+    let result = match command.as_str() {
+        "list" => list(cli),
+        "add" => add(cli),
+        "rm" => rm(cli),
+        "toggle" => toggle(cli),
+        "sync" => sync(cli),
+        "backup" => backup(cli),
+        "restore" => restore(cli),
+        "export" => export(cli),
+        "import" => import(cli),
+        other => {
+            eprintln!("error: unknown command: {}\n\n{}", other, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
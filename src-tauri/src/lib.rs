@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 // ===== Data Models =====
 
@@ -18,6 +18,9 @@ pub struct McpServer {
     pub env: HashMap<String, String>,
     #[serde(default)]
     pub url: String,
+    // Sent with every request to a remote server, e.g. an `Authorization` token
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
     #[serde(default = "default_enabled")]
     pub enabled: bool,
 }
@@ -48,12 +51,193 @@ pub struct ToolInfo {
 pub struct Backup {
     pub name: String,
     pub timestamp: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub trigger: String,
+    #[serde(default)]
+    pub counts: HashMap<String, usize>,
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub encrypted: bool,
+    // Backup directories holding a copy of this file, primary first
+    #[serde(default)]
+    pub origins: Vec<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct BackupMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    // What created the backup: "manual", "pre-write", "pre-sync", "pre-restore",
+    // "pre-import" or "scheduled"
+    #[serde(default = "default_trigger")]
+    pub trigger: String,
+    // Number of servers per tool
+    #[serde(default)]
+    pub counts: HashMap<String, usize>,
+}
+
+fn default_trigger() -> String {
+    "manual".to_string()
+}
+
+// Version 1 is the original `timestamp` + `tools` layout, version 2 adds settings, and
+// version 3 stores plain backups as a `BackupManifest` pointing into the object store
+const BACKUP_FORMAT_VERSION: u32 = 3;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BackupData {
+    #[serde(default = "legacy_backup_version")]
+    pub version: u32,
     pub timestamp: String,
     pub tools: HashMap<String, Vec<McpServer>>,
+    // Verbatim copy of each tool's config file, keyed by tool name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub files: HashMap<String, RawConfigFile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<AppSettings>,
+    #[serde(default)]
+    pub metadata: BackupMetadata,
+}
+
+fn legacy_backup_version() -> u32 {
+    1
+}
+
+// On-disk form of an unencrypted backup. Server lists, raw files and settings are
+// stored once in `objects/` under their SHA-256, so unchanged configs cost nothing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub version: u32,
+    pub timestamp: String,
+    #[serde(default)]
+    pub metadata: BackupMetadata,
+    // Object hash of each tool's server list
+    pub tools: HashMap<String, String>,
+    #[serde(default)]
+    pub files: HashMap<String, ManifestFile>,
+    #[serde(default)]
+    pub settings: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestFile {
+    pub path: String,
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupCheck {
+    pub name: String,
+    pub origin: String,
+    pub ok: bool,
+    pub problems: Vec<String>,
+}
+
+// A passphrase-encrypted `BackupData`. Timestamp and metadata stay readable so
+// encrypted backups can still be listed; server configs and env values do not.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedBundle {
+    pub version: u32,
+    pub timestamp: String,
+    #[serde(default)]
+    pub metadata: BackupMetadata,
+    pub cipher: String,
+    pub kdf: KdfParams,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub algorithm: String,
+    pub salt: String,
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ExportBundle {
+    Encrypted(EncryptedBundle),
+    Plain(BackupData),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawConfigFile {
+    pub path: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerSelection {
+    pub tool: String,
+    pub server: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub commit: String,
+    pub timestamp: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Journal {
+    pub entries: Vec<JournalEntry>,
+    // Entries before the cursor can be undone, entries from it onwards redone
+    pub cursor: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub timestamp: String,
+    pub command: String,
+    pub changes: Vec<JournalChange>,
+}
+
+// Server lists of one tool around a command; undo writes `before`, redo writes `after`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalChange {
+    pub tool: String,
+    pub before: Vec<McpServer>,
+    pub after: Vec<McpServer>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalSummary {
+    pub timestamp: String,
+    pub command: String,
+    pub tools: Vec<String>,
+    pub undone: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolDiff {
+    pub tool: String,
+    pub servers: Vec<ServerDiff>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerDiff {
+    pub name: String,
+    // "added", "removed" or "modified"
+    pub change: String,
+    pub fields: Vec<FieldDiff>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldDiff {
+    pub field: String,
+    pub before: serde_json::Value,
+    pub after: serde_json::Value,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -79,6 +263,276 @@ pub struct AppSettings {
     pub custom_paths: HashMap<String, String>,
     #[serde(default)]
     pub custom_tools: Vec<CustomTool>,
+    #[serde(default)]
+    pub backup_retention: RetentionPolicy,
+    #[serde(default)]
+    pub scheduled_backup: BackupSchedule,
+    // Extra directories manual and scheduled backups are also written to, e.g. a mounted
+    // drive or a synced folder. `~/.mcp-manager/backups` is always the primary destination.
+    #[serde(default)]
+    pub backup_destinations: Vec<String>,
+    // Whether the automatic snapshots taken before each write go to the extra
+    // destinations as well. Off by default: they are frequent and hold every `env`
+    // token in plain text, which shouldn't end up in a synced folder unasked.
+    #[serde(default)]
+    pub snapshot_destinations: bool,
+}
+
+// Applies to the automatic snapshots taken before each write and to scheduled backups,
+// each pruned separately; manual backups are never pruned.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    #[serde(default = "default_keep_last")]
+    pub keep_last: usize,
+    #[serde(default = "default_keep_daily_days")]
+    pub keep_daily_days: i64,
+}
+
+fn default_keep_last() -> usize {
+    20
+}
+
+fn default_keep_daily_days() -> i64 {
+    14
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        RetentionPolicy {
+            keep_last: default_keep_last(),
+            keep_daily_days: default_keep_daily_days(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupSchedule {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_interval_minutes")]
+    pub interval_minutes: i64,
+}
+
+fn default_interval_minutes() -> i64 {
+    60
+}
+
+impl Default for BackupSchedule {
+    fn default() -> Self {
+        BackupSchedule {
+            enabled: false,
+            interval_minutes: default_interval_minutes(),
+        }
+    }
+}
+
+// Payload of the `scheduled-backup` event; `backup` is `None` when nothing had changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledBackupEvent {
+    pub backup: Option<Backup>,
+    pub changed: bool,
+}
+
+// Result of contacting a server and running the MCP `initialize` + `tools/list` exchange
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthCheck {
+    pub server: String,
+    // "ok", "error", "timeout" or "unauthorized" (HTTP 401/403)
+    pub status: String,
+    // "stdio", "streamable-http" or "sse"
+    pub transport: String,
+    // Last HTTP status seen, for URL servers
+    pub http_status: Option<u16>,
+    // Round trip of the `initialize` request
+    pub latency_ms: Option<u64>,
+    // From starting the server (or opening the connection) to the `initialize` response
+    pub startup_ms: Option<u64>,
+    pub protocol_version: Option<String>,
+    pub server_info: Option<serde_json::Value>,
+    pub tool_count: Option<usize>,
+    pub error: Option<String>,
+    // "spawn", "exited", "timeout", "unauthorized", "http", "connection" or "protocol"
+    pub error_class: Option<String>,
+    pub stderr: String,
+    pub duration_ms: u64,
+}
+
+// One health check as kept in ~/.mcp-manager/health.jsonl
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthRecord {
+    pub timestamp: String,
+    // Servers are told apart by tool too: `github` in Cursor isn't `github` in Claude
+    #[serde(default)]
+    pub tool: String,
+    pub server: String,
+    // Changes whenever the server's command, args, env or url change, e.g. on an upgrade
+    pub fingerprint: String,
+    pub transport: String,
+    pub status: String,
+    pub startup_ms: Option<u64>,
+    pub error_class: Option<String>,
+    // `serverInfo.version` reported by the server
+    pub server_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthTimeline {
+    pub tool: String,
+    pub server: String,
+    // Oldest first
+    pub checks: Vec<HealthRecord>,
+    pub success_rate: Option<f64>,
+    pub median_startup_ms: Option<u64>,
+}
+
+// What a server exposes, cached under ~/.mcp-manager/cache by server fingerprint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerInventory {
+    pub server: String,
+    // SHA-256 over the server's command, args, env and url
+    pub fingerprint: String,
+    pub fetched_at: String,
+    pub tools: Vec<serde_json::Value>,
+    pub resources: Vec<serde_json::Value>,
+    pub prompts: Vec<serde_json::Value>,
+    // Set when the server couldn't be contacted; failed fetches are never cached
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub cached: bool,
+}
+
+// One JSON-RPC message as sent to or received from a server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcMessage {
+    // "sent" or "received"
+    pub direction: String,
+    pub message: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InspectorSession {
+    pub session_id: String,
+    pub server: String,
+    pub initialize: serde_json::Value,
+    pub exchange: Vec<RpcMessage>,
+}
+
+// Outcome of one inspector request; `exchange` holds every message sent and received
+// while waiting for it, including notifications and requests from the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InspectorResponse {
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+    pub exchange: Vec<RpcMessage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenBudget {
+    pub tool: String,
+    pub total_tokens: usize,
+    pub tool_count: usize,
+    pub tool_limit: Option<usize>,
+    pub exceeds_limit: bool,
+    // Enabled servers, largest footprint first
+    pub servers: Vec<ServerTokenEstimate>,
+    // Largest individual tool definitions across all servers
+    pub top_tools: Vec<ToolTokenEstimate>,
+    // Enabled servers whose inventory couldn't be read, so they aren't counted
+    pub unavailable: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerTokenEstimate {
+    pub server: String,
+    pub tokens: usize,
+    pub tool_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolTokenEstimate {
+    pub server: String,
+    pub name: String,
+    pub tokens: usize,
+}
+
+// Enabled servers in one AI tool exposing an MCP tool under the same name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCollision {
+    pub tool: String,
+    pub tool_name: String,
+    pub servers: Vec<String>,
+    pub suggestion: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandCheck {
+    pub tool: String,
+    pub server: String,
+    pub command: String,
+    // "found", "missing" or "not-executable"
+    pub status: String,
+    pub resolved_path: Option<String>,
+    // "node", "uv", "python", "docker", "bun" or "deno" for commands that need one
+    pub runtime: Option<String>,
+    pub runtime_version: Option<String>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathFinding {
+    pub tool: String,
+    pub server: String,
+    // "command", "args[N]" or "env.NAME"
+    pub field: String,
+    pub path: String,
+    // "missing", "placeholder" or "relative"
+    pub problem: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoctorFinding {
+    // "error", "warning" or "info"
+    pub severity: String,
+    // "missing-file", "permission", "unparseable", "duplicate-name", "missing-command",
+    // "unset-env", "unsupported-field" or "stale-path"
+    pub check: String,
+    pub tool: String,
+    pub server: Option<String>,
+    pub message: String,
+    pub fix: String,
+}
+
+// One line of a recorded transcript. `sent` is what the client sent to the server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    pub timestamp: String,
+    pub direction: String,
+    pub message: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptInfo {
+    pub name: String,
+    pub messages: usize,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayStep {
+    pub request: serde_json::Value,
+    // Responses are `None` for notifications
+    pub recorded: Option<serde_json::Value>,
+    pub replayed: Option<serde_json::Value>,
+    // Whether `result` or `error` came back the same as recorded
+    pub matches: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptReplay {
+    pub steps: Vec<ReplayStep>,
+    pub stderr: String,
 }
 
 // ===== Tool Definitions =====
@@ -88,6 +542,10 @@ struct ToolDef {
     display_name: &'static str,
     config_key: &'static str,
     format: &'static str,
+    // Most tools a client will pass to the model, where the client documents a limit
+    tool_limit: Option<usize>,
+    // Server entry fields the client documents
+    server_fields: &'static [&'static str],
     path_fn: fn(&PathBuf) -> PathBuf,
 }
 
@@ -98,6 +556,8 @@ fn get_tool_definitions() -> Vec<ToolDef> {
             display_name: "Claude Code",
             config_key: "mcpServers",
             format: "json",
+            tool_limit: None,
+            server_fields: &["type", "command", "args", "env", "url", "headers"],
             path_fn: |home| home.join(".claude.json"),
         },
         ToolDef {
@@ -105,6 +565,8 @@ fn get_tool_definitions() -> Vec<ToolDef> {
             display_name: "Gemini CLI",
             config_key: "mcpServers",
             format: "json",
+            tool_limit: None,
+            server_fields: &["command", "args", "env", "cwd", "url", "httpUrl", "headers", "timeout", "trust", "includeTools", "excludeTools", "description"],
             path_fn: |home| home.join(".gemini").join("settings.json"),
         },
         ToolDef {
@@ -112,6 +574,8 @@ fn get_tool_definitions() -> Vec<ToolDef> {
             display_name: "Codex CLI",
             config_key: "mcp_servers",
             format: "toml",
+            tool_limit: None,
+            server_fields: &["command", "args", "env", "cwd", "url", "bearer_token_env_var", "http_headers", "env_http_headers", "startup_timeout_sec", "tool_timeout_sec", "enabled", "enabled_tools", "disabled_tools"],
             path_fn: |home| home.join(".codex").join("config.toml"),
         },
         ToolDef {
@@ -119,6 +583,8 @@ fn get_tool_definitions() -> Vec<ToolDef> {
             display_name: "Copilot CLI",
             config_key: "mcpServers",
             format: "json",
+            tool_limit: None,
+            server_fields: &["type", "command", "args", "env", "cwd", "url", "headers", "tools"],
             path_fn: |home| home.join(".copilot").join("mcp-config.json"),
         },
         ToolDef {
@@ -126,6 +592,8 @@ fn get_tool_definitions() -> Vec<ToolDef> {
             display_name: "VS Code",
             config_key: "servers",
             format: "json",
+            tool_limit: Some(128),
+            server_fields: &["type", "command", "args", "env", "envFile", "cwd", "url", "headers", "dev"],
             path_fn: |home| home.join("Library/Application Support/Code/User/mcp.json"),
         },
         ToolDef {
@@ -133,6 +601,8 @@ fn get_tool_definitions() -> Vec<ToolDef> {
             display_name: "Cursor",
             config_key: "mcpServers",
             format: "json",
+            tool_limit: Some(40),
+            server_fields: &["type", "command", "args", "env", "envFile", "url", "headers"],
             path_fn: |home| home.join("Library/Application Support/Cursor/User/mcp.json"),
         },
         ToolDef {
@@ -140,6 +610,8 @@ fn get_tool_definitions() -> Vec<ToolDef> {
             display_name: "VS Code Insiders",
             config_key: "servers",
             format: "json",
+            tool_limit: Some(128),
+            server_fields: &["type", "command", "args", "env", "envFile", "cwd", "url", "headers", "dev"],
             path_fn: |home| home.join("Library/Application Support/Code - Insiders/User/mcp.json"),
         },
         ToolDef {
@@ -147,6 +619,8 @@ fn get_tool_definitions() -> Vec<ToolDef> {
             display_name: "Windsurf",
             config_key: "mcpServers",
             format: "json",
+            tool_limit: Some(100),
+            server_fields: &["command", "args", "env", "serverUrl", "url", "headers", "disabled", "disabledTools"],
             path_fn: |home| home.join(".codeium/windsurf/mcp_config.json"),
         },
     ]
@@ -183,6 +657,65 @@ fn save_settings(settings: &AppSettings) -> Result<(), String> {
     fs::write(&path, content).map_err(|e| e.to_string())
 }
 
+// Expands a leading `~` to the home directory
+fn expand_home(path: &str) -> PathBuf {
+    if path == "~" {
+        get_home_dir()
+    } else if let Some(rest) = path.strip_prefix("~/") {
+        get_home_dir().join(rest)
+    } else {
+        PathBuf::from(path)
+    }
+}
+
+// Checks a user-supplied config path before it is stored in settings. Tool config
+// files get overwritten, so the path must be absolute, free of `..`, not a directory,
+// and outside MCP Manager's own data directory.
+fn validate_config_path(path: &str) -> Result<PathBuf, String> {
+    let expanded = expand_home(path.trim());
+    
+    if !expanded.is_absolute() {
+        return Err(format!("Config path must be absolute or start with ~/: {}", path));
+    }
+    if expanded.components().any(|c| c == std::path::Component::ParentDir) {
+        return Err(format!("Config path must not contain '..': {}", path));
+    }
+    if expanded.is_dir() {
+        return Err(format!("Config path is a directory: {}", path));
+    }
+    
+    let data_dir = get_home_dir().join(".mcp-manager");
+    let resolved = resolve_existing_prefix(&expanded);
+    let data_dir = fs::canonicalize(&data_dir).unwrap_or(data_dir);
+    if resolved.starts_with(&data_dir) {
+        return Err(format!("Config path must not be inside {}", data_dir.to_string_lossy()));
+    }
+    
+    Ok(expanded)
+}
+
+// Canonicalizes the longest existing ancestor of `path`, so symlinked directories are
+// resolved even when the file itself doesn't exist yet
+fn resolve_existing_prefix(path: &std::path::Path) -> PathBuf {
+    let mut existing = path.to_path_buf();
+    let mut rest = vec![];
+    while !existing.exists() {
+        match (existing.file_name().map(|n| n.to_os_string()), existing.parent()) {
+            (Some(name), Some(parent)) => {
+                rest.push(name);
+                existing = parent.to_path_buf();
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+    
+    let mut resolved = fs::canonicalize(&existing).unwrap_or(existing);
+    for name in rest.into_iter().rev() {
+        resolved.push(name);
+    }
+    resolved
+}
+
 fn get_tool_path(tool_name: &str) -> Option<(PathBuf, String, String)> {
     let home = get_home_dir();
     let settings = load_settings();
@@ -192,19 +725,9 @@ fn get_tool_path(tool_name: &str) -> Option<(PathBuf, String, String)> {
     for def in defs {
         if def.name == tool_name {
             // Check for custom path first
-            let path = if let Some(custom_path) = settings.custom_paths.get(tool_name) {
-                if !custom_path.is_empty() {
-                    // Expand ~ to home directory
-                    if custom_path.starts_with("~") {
-                        home.join(custom_path.trim_start_matches("~/"))
-                    } else {
-                        PathBuf::from(custom_path)
-                    }
-                } else {
-                    (def.path_fn)(&home)
-                }
-            } else {
-                (def.path_fn)(&home)
+            let path = match settings.custom_paths.get(tool_name) {
+                Some(custom_path) if !custom_path.is_empty() => expand_home(custom_path),
+                _ => (def.path_fn)(&home),
             };
             return Some((path, def.config_key.to_string(), def.format.to_string()));
         }
@@ -213,11 +736,7 @@ fn get_tool_path(tool_name: &str) -> Option<(PathBuf, String, String)> {
     // Check custom tools from settings
     for custom_tool in &settings.custom_tools {
         if custom_tool.name == tool_name {
-            let path = if custom_tool.config_path.starts_with("~") {
-                home.join(custom_tool.config_path.trim_start_matches("~/"))
-            } else {
-                PathBuf::from(&custom_tool.config_path)
-            };
+            let path = expand_home(&custom_tool.config_path);
             return Some((path, custom_tool.config_key.clone(), custom_tool.format.clone()));
         }
     }
@@ -225,10 +744,53 @@ fn get_tool_path(tool_name: &str) -> Option<(PathBuf, String, String)> {
     None
 }
 
+fn config_exists(tool_name: &str) -> bool {
+    get_tool_path(tool_name).map(|(path, _, _)| path.exists()).unwrap_or(false)
+}
+
 fn get_backup_dir() -> PathBuf {
     get_home_dir().join(".mcp-manager").join("backups")
 }
 
+// The directories automatic snapshots are written to and pruned in
+fn get_snapshot_dirs() -> Vec<PathBuf> {
+    if load_settings().snapshot_destinations {
+        get_backup_dirs()
+    } else {
+        vec![get_backup_dir()]
+    }
+}
+
+// The primary backup directory followed by the configured extra destinations
+fn get_backup_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![get_backup_dir()];
+    for destination in load_settings().backup_destinations {
+        let dir = expand_home(&destination);
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    dirs
+}
+
+// Destinations get written to but never hold tool configs, so they only need to be
+// absolute, free of `..`, and not an existing file
+fn validate_backup_destination(path: &str) -> Result<PathBuf, String> {
+    let expanded = expand_home(path.trim());
+    
+    if !expanded.is_absolute() {
+        return Err(format!("Backup destination must be absolute or start with ~/: {}", path));
+    }
+    if expanded.components().any(|c| c == std::path::Component::ParentDir) {
+        return Err(format!("Backup destination must not contain '..': {}", path));
+    }
+    if expanded.is_file() {
+        return Err(format!("Backup destination is a file: {}", path));
+    }
+    
+    Ok(expanded)
+}
+
 // ===== JSON Config Parsing =====
 
 fn read_json_servers(path: &PathBuf, key: &str) -> Vec<McpServer> {
@@ -276,6 +838,10 @@ fn read_json_servers(path: &PathBuf, key: &str) -> Vec<McpServer> {
                 .map(|obj| obj.iter().filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string()))).collect())
                 .unwrap_or_default(),
             url: server.get("url").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            headers: server.get("headers")
+                .and_then(|v| v.as_object())
+                .map(|obj| obj.iter().filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string()))).collect())
+                .unwrap_or_default(),
             enabled: !server.get("disabled").and_then(|v| v.as_bool()).unwrap_or(false),
         }
     }).collect()
@@ -303,6 +869,9 @@ fn write_json_servers(path: &PathBuf, key: &str, servers: &[McpServer]) -> Resul
             }
         } else {
             server_config.insert("url".to_string(), serde_json::json!(server.url));
+            if !server.headers.is_empty() {
+                server_config.insert("headers".to_string(), serde_json::json!(server.headers));
+            }
         }
         
         if !server.env.is_empty() {
@@ -362,6 +931,11 @@ fn read_toml_servers(path: &PathBuf) -> Vec<McpServer> {
                 .map(|t| t.iter().filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string()))).collect())
                 .unwrap_or_default(),
             url: server.get("url").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            // Codex calls them `http_headers`
+            headers: server.get("http_headers")
+                .and_then(|v| v.as_table())
+                .map(|t| t.iter().filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string()))).collect())
+                .unwrap_or_default(),
             enabled: !server.get("disabled").and_then(|v| v.as_bool()).unwrap_or(false),
         }
     }).collect()
@@ -391,6 +965,12 @@ fn write_toml_servers(path: &PathBuf, servers: &[McpServer]) -> Result<(), Strin
             }
         } else {
             server_config.insert("url".to_string(), toml::Value::String(server.url.clone()));
+            if !server.headers.is_empty() {
+                let headers_table: toml::map::Map<String, toml::Value> = server.headers.iter()
+                    .map(|(k, v)| (k.clone(), toml::Value::String(v.clone())))
+                    .collect();
+                server_config.insert("http_headers".to_string(), toml::Value::Table(headers_table));
+            }
         }
         
         if !server.env.is_empty() {
@@ -441,6 +1021,34 @@ fn write_servers(tool: &str, servers: &[McpServer]) -> Result<(), String> {
     }
 }
 
+fn read_raw_config(tool: &str) -> Option<RawConfigFile> {
+    let (path, _, _) = get_tool_path(tool)?;
+    let content = fs::read_to_string(&path).ok()?;
+    Some(RawConfigFile {
+        path: path.to_string_lossy().to_string(),
+        content,
+    })
+}
+
+fn read_raw_configs<S: AsRef<str>>(tools: &[S]) -> HashMap<String, RawConfigFile> {
+    tools.iter()
+        .filter_map(|tool| read_raw_config(tool.as_ref()).map(|raw| (tool.as_ref().to_string(), raw)))
+        .collect()
+}
+
+// Puts a config file back byte for byte, at the tool's current path
+fn write_raw_config(tool: &str, content: &str) -> Result<(), String> {
+    let (path, _, _) = match get_tool_path(tool) {
+        Some(p) => p,
+        None => return Err(format!("Unknown tool: {}", tool)),
+    };
+    
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(&path, content).map_err(|e| e.to_string())
+}
+
 fn get_all_configs_internal() -> HashMap<String, Vec<McpServer>> {
     let mut all = HashMap::new();
     let settings = load_settings();
@@ -458,34 +1066,2893 @@ fn get_all_configs_internal() -> HashMap<String, Vec<McpServer>> {
     all
 }
 
-// ===== Tauri Commands Module =====
-mod commands {
-    use super::*;
+// ===== Backup Files =====
 
-    #[tauri::command]
-    pub fn get_tools() -> Vec<ToolInfo> {
-        let home = get_home_dir();
-        let settings = load_settings();
-        
-        let mut tools: Vec<ToolInfo> = get_tool_definitions().iter().map(|def| {
-            let default_path = (def.path_fn)(&home);
-            let custom_path = settings.custom_paths.get(def.name);
-            let is_custom = custom_path.map(|p| !p.is_empty()).unwrap_or(false);
-            
-            let actual_path = if is_custom {
-                let cp = custom_path.unwrap();
-                if cp.starts_with("~") {
-                    home.join(cp.trim_start_matches("~/"))
-                } else {
-                    PathBuf::from(cp)
-                }
-            } else {
-                default_path
-            };
-            
-            ToolInfo {
-                name: def.name.to_string(),
-                display_name: def.display_name.to_string(),
+const SNAPSHOT_PREFIX: &str = "snapshot-";
+const SCHEDULED_PREFIX: &str = "scheduled-";
+
+// Objects younger than this are never collected: they may belong to a backup that
+// another process (the CLI, a second app instance) hasn't written the manifest of yet
+const GC_GRACE_PERIOD_SECS: u64 = 600;
+
+// Serializes writing, pruning and garbage collecting backups within this process. The
+// scheduler thread and commands both do these, and a collection running between
+// `put_object` and the manifest write would delete the new backup's objects.
+static BACKUP_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+fn lock_backups() -> std::sync::MutexGuard<'static, ()> {
+    // The lock guards no data, so a panic while it was held leaves nothing inconsistent
+    BACKUP_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// Sub-second precision for every backup name, so backups made within one second neither
+// overwrite each other nor sort out of order
+fn backup_timestamp() -> String {
+    chrono::Local::now().format("%Y-%m-%dT%H-%M-%S%.6f").to_string()
+}
+
+fn backup_timestamp_from_name(name: &str) -> String {
+    name.trim_start_matches("backup-")
+        .trim_start_matches(SNAPSHOT_PREFIX)
+        .trim_start_matches(SCHEDULED_PREFIX)
+        .trim_end_matches(".json")
+        .to_string()
+}
+
+fn backup_metadata(tools: &HashMap<String, Vec<McpServer>>, trigger: &str, label: Option<String>, note: Option<String>) -> BackupMetadata {
+    BackupMetadata {
+        label,
+        note,
+        trigger: trigger.to_string(),
+        counts: tools.iter().map(|(tool, servers)| (tool.clone(), servers.len())).collect(),
+    }
+}
+
+fn write_backup_file<T: Serialize>(backup_dir: &Path, filename: &str, data: &T) -> Result<(), String> {
+    fs::create_dir_all(backup_dir).map_err(|e| e.to_string())?;
+    let content = serde_json::to_string_pretty(data).map_err(|e| e.to_string())?;
+    fs::write(backup_dir.join(filename), content).map_err(|e| e.to_string())
+}
+
+// ===== Backup Object Store =====
+
+// Each backup directory has its own object store, so every destination can be copied
+// or restored from on its own
+fn get_objects_dir(backup_dir: &Path) -> PathBuf {
+    backup_dir.join("objects")
+}
+
+fn hash_bytes(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+// Serializing through `serde_json::Value` sorts map keys, so equal configs always
+// produce the same bytes and therefore the same object
+fn canonical_json<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
+    let value = serde_json::to_value(value).map_err(|e| e.to_string())?;
+    serde_json::to_vec(&value).map_err(|e| e.to_string())
+}
+
+fn put_object(backup_dir: &Path, bytes: &[u8]) -> Result<String, String> {
+    let hash = hash_bytes(bytes);
+    let objects_dir = get_objects_dir(backup_dir);
+    let path = objects_dir.join(&hash);
+    if path.exists() {
+        return Ok(hash);
+    }
+    
+    fs::create_dir_all(&objects_dir).map_err(|e| e.to_string())?;
+    // Write then rename, so an interrupted write never leaves a truncated object behind
+    let tmp = objects_dir.join(format!("{}.tmp", hash));
+    fs::write(&tmp, bytes).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, &path).map_err(|e| e.to_string())?;
+    Ok(hash)
+}
+
+fn get_object(backup_dir: &Path, hash: &str) -> Result<Vec<u8>, String> {
+    let bytes = fs::read(get_objects_dir(backup_dir).join(hash))
+        .map_err(|e| format!("Backup object {} is missing: {}", hash, e))?;
+    if hash_bytes(&bytes) != hash {
+        return Err(format!("Backup object {} is corrupted", hash));
+    }
+    Ok(bytes)
+}
+
+fn write_manifest(backup_dir: &Path, filename: &str, data: &BackupData) -> Result<(), String> {
+    let mut tools = HashMap::new();
+    for (tool, servers) in &data.tools {
+        tools.insert(tool.clone(), put_object(backup_dir, &canonical_json(servers)?)?);
+    }
+    
+    let mut files = HashMap::new();
+    for (tool, raw) in &data.files {
+        let hash = put_object(backup_dir, raw.content.as_bytes())?;
+        files.insert(tool.clone(), ManifestFile { path: raw.path.clone(), hash });
+    }
+    
+    let settings = match data.settings {
+        Some(ref settings) => Some(put_object(backup_dir, &canonical_json(settings)?)?),
+        None => None,
+    };
+    
+    let manifest = BackupManifest {
+        version: BACKUP_FORMAT_VERSION,
+        timestamp: data.timestamp.clone(),
+        metadata: data.metadata.clone(),
+        tools,
+        files,
+        settings,
+    };
+    write_backup_file(backup_dir, filename, &manifest)
+}
+
+fn resolve_manifest(backup_dir: &Path, manifest: BackupManifest) -> Result<BackupData, String> {
+    let mut tools = HashMap::new();
+    for (tool, hash) in manifest.tools {
+        let servers = serde_json::from_slice(&get_object(backup_dir, &hash)?).map_err(|e| e.to_string())?;
+        tools.insert(tool, servers);
+    }
+    
+    let mut files = HashMap::new();
+    for (tool, file) in manifest.files {
+        let content = String::from_utf8(get_object(backup_dir, &file.hash)?).map_err(|e| e.to_string())?;
+        files.insert(tool, RawConfigFile { path: file.path, content });
+    }
+    
+    let settings = match manifest.settings {
+        Some(hash) => Some(serde_json::from_slice(&get_object(backup_dir, &hash)?).map_err(|e| e.to_string())?),
+        None => None,
+    };
+    
+    Ok(BackupData {
+        version: manifest.version,
+        timestamp: manifest.timestamp,
+        tools,
+        files,
+        settings,
+        metadata: manifest.metadata,
+    })
+}
+
+fn manifest_objects(manifest: &BackupManifest) -> Vec<String> {
+    let mut hashes: Vec<String> = manifest.tools.values().cloned()
+        .chain(manifest.files.values().map(|f| f.hash.clone()))
+        .chain(manifest.settings.iter().cloned())
+        .collect();
+    hashes.sort();
+    hashes.dedup();
+    hashes
+}
+
+// Returns `None` for legacy and encrypted backups, which are self-contained files
+fn read_manifest(path: &PathBuf) -> Option<BackupManifest> {
+    let content = fs::read_to_string(path).ok()?;
+    let value: serde_json::Value = serde_json::from_str(&content).ok()?;
+    if value.get("ciphertext").is_some() || value.get("version").and_then(|v| v.as_u64()).unwrap_or(1) < 3 {
+        return None;
+    }
+    serde_json::from_value(value).ok()
+}
+
+fn list_backup_files(backup_dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(backup_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension().map(|ext| ext == "json").unwrap_or(false))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Removes objects no longer referenced by any manifest in the same backup directory.
+// Callers hold `lock_backups()`.
+fn collect_garbage(backup_dir: &Path) -> Result<usize, String> {
+    let objects_dir = get_objects_dir(backup_dir);
+    if !objects_dir.exists() {
+        return Ok(0);
+    }
+    
+    let referenced: std::collections::HashSet<String> = list_backup_files(backup_dir).iter()
+        .filter_map(read_manifest)
+        .flat_map(|m| manifest_objects(&m))
+        .collect();
+    
+    let grace_period = std::time::Duration::from_secs(GC_GRACE_PERIOD_SECS);
+    let is_recent = |entry: &fs::DirEntry| {
+        entry.metadata()
+            .and_then(|m| m.modified())
+            .map(|modified| modified.elapsed().map(|age| age < grace_period).unwrap_or(true))
+            .unwrap_or(true)
+    };
+    
+    let mut removed = 0;
+    for entry in fs::read_dir(&objects_dir).map_err(|e| e.to_string())?.filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        // `.tmp` files are objects still being written
+        if !referenced.contains(&name) && !name.ends_with(".tmp") && !is_recent(&entry) {
+            fs::remove_file(entry.path()).map_err(|e| e.to_string())?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+fn verify_backup(backup_dir: &Path, path: &PathBuf) -> BackupCheck {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let mut problems = vec![];
+    
+    match read_manifest(path) {
+        Some(manifest) => {
+            for hash in manifest_objects(&manifest) {
+                if let Err(e) = get_object(backup_dir, &hash) {
+                    problems.push(e);
+                }
+            }
+        }
+        // Self-contained files can only be checked for being well-formed JSON here;
+        // encrypted ones are authenticated when decrypted
+        None => {
+            let parsed = fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).map_err(|e| e.to_string()));
+            if let Err(e) = parsed {
+                problems.push(e);
+            }
+        }
+    }
+    
+    BackupCheck {
+        name,
+        origin: backup_dir.to_string_lossy().to_string(),
+        ok: problems.is_empty(),
+        problems,
+    }
+}
+
+// ===== Backups =====
+
+// Only the header of the file is needed for listings
+#[derive(Deserialize)]
+struct BackupHeader {
+    timestamp: String,
+    #[serde(default)]
+    metadata: BackupMetadata,
+    #[serde(default)]
+    cipher: Option<String>,
+}
+
+fn read_backup_listing(backup_dir: &Path, path: &PathBuf) -> Backup {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let origins = vec![backup_dir.to_string_lossy().to_string()];
+    let mut size = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    // Count the objects a manifest points at too (shared objects are counted per backup)
+    if let Some(manifest) = read_manifest(path) {
+        size += manifest_objects(&manifest).iter()
+            .filter_map(|hash| fs::metadata(get_objects_dir(backup_dir).join(hash)).ok())
+            .map(|m| m.len())
+            .sum::<u64>();
+    }
+    let header = fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<BackupHeader>(&content).ok());
+    
+    match header {
+        Some(header) => Backup {
+            name,
+            timestamp: header.timestamp,
+            label: header.metadata.label,
+            note: header.metadata.note,
+            trigger: header.metadata.trigger,
+            counts: header.metadata.counts,
+            size,
+            encrypted: header.cipher.is_some(),
+            origins,
+        },
+        // Unreadable files are still listed so they can be deleted
+        None => Backup {
+            timestamp: backup_timestamp_from_name(&name),
+            name,
+            label: None,
+            note: None,
+            trigger: String::new(),
+            counts: HashMap::new(),
+            size,
+            encrypted: false,
+            origins,
+        },
+    }
+}
+
+// Listings from every backup directory, merged by file name. Copies of the same backup
+// are expected to be identical, so the first one found (primary first) is listed.
+fn list_backups() -> Vec<Backup> {
+    let mut backups: Vec<Backup> = vec![];
+    for backup_dir in get_backup_dirs() {
+        for path in list_backup_files(&backup_dir) {
+            let listing = read_backup_listing(&backup_dir, &path);
+            match backups.iter_mut().find(|b| b.name == listing.name) {
+                Some(existing) => existing.origins.extend(listing.origins),
+                None => backups.push(listing),
+            }
+        }
+    }
+    backups
+}
+
+// A backup file name coming from the frontend, checked to name a file that
+// `get_backups` lists and that resolves inside its backup directory. Holds every
+// backup directory with a copy of the file.
+pub struct BackupId(String, Vec<PathBuf>);
+
+impl BackupId {
+    pub fn parse(name: &str) -> Result<BackupId, String> {
+        let is_plain_name = !name.is_empty()
+            && !name.starts_with('.')
+            && !name.contains(['/', '\\', '\0'])
+            && name.ends_with(".json");
+        if !is_plain_name {
+            return Err(format!("Invalid backup name: {}", name));
+        }
+        
+        let mut origins = vec![];
+        for backup_dir in get_backup_dirs() {
+            let listed = list_backup_files(&backup_dir).iter()
+                .any(|p| p.file_name().map(|n| n == name).unwrap_or(false));
+            if !listed {
+                continue;
+            }
+            
+            // Catches symlinks pointing out of the backup directory
+            let canonical_dir = fs::canonicalize(&backup_dir).map_err(|e| e.to_string())?;
+            let resolved = fs::canonicalize(canonical_dir.join(name)).map_err(|e| e.to_string())?;
+            if resolved.parent() != Some(canonical_dir.as_path()) {
+                return Err(format!("Backup {} resolves outside the backup directory", name));
+            }
+            origins.push(backup_dir);
+        }
+        
+        if origins.is_empty() {
+            return Err(format!("Backup not found: {}", name));
+        }
+        Ok(BackupId(name.to_string(), origins))
+    }
+    
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+    
+    fn origins(&self) -> &[PathBuf] {
+        &self.1
+    }
+}
+
+// Reads the first copy that loads, so a backup survives as long as one destination
+// still holds it intact
+fn load_backup(id: &BackupId, passphrase: Option<&str>) -> Result<BackupData, String> {
+    let mut last_error = format!("Backup not found: {}", id.name());
+    for backup_dir in id.origins() {
+        match load_backup_from(backup_dir, id.name(), passphrase) {
+            Ok(data) => return Ok(data),
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+fn load_backup_from(backup_dir: &Path, filename: &str, passphrase: Option<&str>) -> Result<BackupData, String> {
+    let content = fs::read_to_string(backup_dir.join(filename)).map_err(|e| e.to_string())?;
+    let value: serde_json::Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    
+    let version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(1);
+    if version > BACKUP_FORMAT_VERSION as u64 {
+        return Err(format!(
+            "Backup {} uses format version {}, this version of MCP Manager supports up to {}",
+            filename, version, BACKUP_FORMAT_VERSION
+        ));
+    }
+    
+    if value.get("ciphertext").is_some() {
+        let bundle: EncryptedBundle = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        let passphrase = passphrase
+            .ok_or_else(|| format!("Backup {} is encrypted, a passphrase is required", filename))?;
+        decrypt_backup(&bundle, passphrase)
+    } else if version >= 3 {
+        let manifest: BackupManifest = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        resolve_manifest(backup_dir, manifest)
+    } else {
+        serde_json::from_value(value).map_err(|e| e.to_string())
+    }
+}
+
+// Encrypted backups are written as one self-contained file: putting their contents
+// in the shared object store would leave them readable in plain text.
+// Snapshots only go to the extra destinations when `snapshot_destinations` is set.
+// Only a failure to write the primary copy is an error; extra destinations may be
+// unmounted or offline, so those failures are logged and skipped.
+fn store_backup(filename: &str, data: &BackupData, passphrase: Option<&str>) -> Result<(), String> {
+    let bundle = match passphrase {
+        Some(passphrase) => Some(encrypt_backup(data, passphrase)?),
+        None => None,
+    };
+    
+    let backup_dirs = match data.metadata.trigger.as_str() {
+        "manual" | "scheduled" => get_backup_dirs(),
+        _ => get_snapshot_dirs(),
+    };
+    
+    let _lock = lock_backups();
+    for (idx, backup_dir) in backup_dirs.iter().enumerate() {
+        let result = match bundle {
+            Some(ref bundle) => write_backup_file(backup_dir, filename, bundle),
+            None => write_manifest(backup_dir, filename, data),
+        };
+        match result {
+            Err(e) if idx == 0 => return Err(e),
+            Err(e) => log::warn!("Failed to write backup {} to {}: {}", filename, backup_dir.to_string_lossy(), e),
+            Ok(()) => {}
+        }
+    }
+    Ok(())
+}
+
+// Restores the custom path and custom tool definition of each tool being restored, so
+// `write_servers` can resolve them even on a machine that has never seen those tools.
+// Settings for tools outside the restore are left alone.
+fn restore_tool_settings(backed_up: &AppSettings, tools: &[String]) -> Result<(), String> {
+    let mut settings = load_settings();
+    
+    // Backup files can be edited by hand, so their paths get the same checks as new ones
+    for tool in tools {
+        if let Some(path) = backed_up.custom_paths.get(tool).filter(|p| !p.is_empty()) {
+            validate_config_path(path)?;
+        }
+        if let Some(custom_tool) = backed_up.custom_tools.iter().find(|t| &t.name == tool) {
+            validate_config_path(&custom_tool.config_path)?;
+        }
+    }
+    
+    for tool in tools {
+        match backed_up.custom_paths.get(tool) {
+            Some(path) => settings.custom_paths.insert(tool.clone(), path.clone()),
+            None => settings.custom_paths.remove(tool),
+        };
+        
+        if let Some(custom_tool) = backed_up.custom_tools.iter().find(|t| &t.name == tool) {
+            match settings.custom_tools.iter().position(|t| &t.name == tool) {
+                Some(idx) => settings.custom_tools[idx] = custom_tool.clone(),
+                None => settings.custom_tools.push(custom_tool.clone()),
+            }
+        }
+    }
+    
+    save_settings(&settings)
+}
+
+// Called by every mutating command before it touches a tool file, so the previous
+// state of those tools can always be restored through `restore_backup`.
+fn snapshot_before_write<S: AsRef<str>>(tools: &[S], trigger: &str) -> Result<(), String> {
+    prepare_history(tools);
+    
+    let mut snapshot = HashMap::new();
+    for tool in tools.iter().map(|t| t.as_ref()) {
+        // Nothing to lose if the file doesn't exist yet
+        if config_exists(tool) {
+            snapshot.insert(tool.to_string(), read_servers(tool));
+        }
+    }
+    if snapshot.is_empty() {
+        return Ok(());
+    }
+    
+    let timestamp = backup_timestamp();
+    let filename = format!("{}{}.json", SNAPSHOT_PREFIX, timestamp);
+    let tool_names: Vec<String> = snapshot.keys().cloned().collect();
+    let data = BackupData {
+        version: BACKUP_FORMAT_VERSION,
+        timestamp,
+        metadata: backup_metadata(&snapshot, trigger, None, None),
+        files: read_raw_configs(&tool_names),
+        tools: snapshot,
+        settings: Some(load_settings()),
+    };
+    store_backup(&filename, &data, None)
+        .map_err(|e| format!("Failed to snapshot configs before writing: {}", e))?;
+    
+    if let Err(e) = prune_snapshots(&load_settings().backup_retention) {
+        log::warn!("Failed to prune snapshots: {}", e);
+    }
+    Ok(())
+}
+
+// Each backup directory is pruned on its own, so a destination that was offline for a
+// while catches up the next time it's reachable. Snapshots are only pruned where they
+// are written.
+fn prune_snapshots(policy: &RetentionPolicy) -> Result<Vec<String>, String> {
+    let snapshot_dirs = get_snapshot_dirs();
+    let _lock = lock_backups();
+    let mut all_pruned = vec![];
+    for backup_dir in get_backup_dirs() {
+        let mut pruned = prune_backups_with_prefix(&backup_dir, SCHEDULED_PREFIX, policy)?;
+        if snapshot_dirs.contains(&backup_dir) {
+            pruned.extend(prune_backups_with_prefix(&backup_dir, SNAPSHOT_PREFIX, policy)?);
+        }
+        
+        if !pruned.is_empty() {
+            collect_garbage(&backup_dir)?;
+        }
+        all_pruned.extend(pruned);
+    }
+    
+    all_pruned.sort();
+    all_pruned.dedup();
+    Ok(all_pruned)
+}
+
+fn prune_backups_with_prefix(backup_dir: &Path, prefix: &str, policy: &RetentionPolicy) -> Result<Vec<String>, String> {
+    if !backup_dir.exists() {
+        return Ok(vec![]);
+    }
+    
+    let mut snapshots: Vec<(String, chrono::NaiveDateTime)> = fs::read_dir(backup_dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .filter(|name| name.starts_with(prefix) && name.ends_with(".json"))
+        .filter_map(|name| {
+            let timestamp = backup_timestamp_from_name(&name);
+            chrono::NaiveDateTime::parse_from_str(&timestamp, "%Y-%m-%dT%H-%M-%S%.f")
+                .ok()
+                .map(|t| (name, t))
+        })
+        .collect();
+    
+    // Newest first, so the first snapshot seen for a day is the one kept for it
+    snapshots.sort_by_key(|(_, time)| std::cmp::Reverse(*time));
+    
+    let daily_cutoff = chrono::Local::now().naive_local().date() - chrono::Duration::days(policy.keep_daily_days);
+    let mut kept_days = std::collections::HashSet::new();
+    let mut pruned = vec![];
+    
+    for (idx, (name, time)) in snapshots.iter().enumerate() {
+        let day = time.date();
+        let keep_recent = idx < policy.keep_last;
+        let keep_daily = day > daily_cutoff && kept_days.insert(day);
+        
+        if !keep_recent && !keep_daily {
+            fs::remove_file(backup_dir.join(name)).map_err(|e| e.to_string())?;
+            pruned.push(name.clone());
+        }
+    }
+    
+    Ok(pruned)
+}
+
+// Gathers every tool's servers, raw config file and the settings, as `create_backup` saves them
+fn gather_backup_data(timestamp: String, trigger: &str, label: Option<String>, note: Option<String>) -> BackupData {
+    let tools = get_all_configs_internal();
+    let tool_names: Vec<String> = tools.keys().cloned().collect();
+    BackupData {
+        version: BACKUP_FORMAT_VERSION,
+        timestamp,
+        metadata: backup_metadata(&tools, trigger, label, note),
+        files: read_raw_configs(&tool_names),
+        tools,
+        settings: Some(load_settings()),
+    }
+}
+
+// ===== Scheduled Backups =====
+
+// Whether the newest unencrypted backup covering the same tools already holds exactly
+// this state, comparing object hashes without writing anything
+#[cfg(feature = "gui")]
+fn matches_latest_backup(data: &BackupData) -> Result<bool, String> {
+    let mut tools = HashMap::new();
+    for (tool, servers) in &data.tools {
+        tools.insert(tool.clone(), hash_bytes(&canonical_json(servers)?));
+    }
+    let files: HashMap<String, String> = data.files.iter()
+        .map(|(tool, raw)| (tool.clone(), hash_bytes(raw.content.as_bytes())))
+        .collect();
+    let settings = match data.settings {
+        Some(ref settings) => Some(hash_bytes(&canonical_json(settings)?)),
+        None => None,
+    };
+    
+    let latest = list_backup_files(&get_backup_dir()).iter()
+        .filter_map(read_manifest)
+        .filter(|m| m.tools.len() == tools.len() && m.tools.keys().all(|t| tools.contains_key(t)))
+        .max_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    
+    Ok(latest.map(|m| {
+        let manifest_files: HashMap<String, String> = m.files.into_iter().map(|(t, f)| (t, f.hash)).collect();
+        m.tools == tools && manifest_files == files && m.settings == settings
+    }).unwrap_or(false))
+}
+
+#[cfg(feature = "gui")]
+fn run_scheduled_backup() -> Result<ScheduledBackupEvent, String> {
+    let timestamp = backup_timestamp();
+    let data = gather_backup_data(timestamp.clone(), "scheduled", None, None);
+    if matches_latest_backup(&data)? {
+        return Ok(ScheduledBackupEvent { backup: None, changed: false });
+    }
+    
+    let filename = format!("{}{}.json", SCHEDULED_PREFIX, timestamp);
+    store_backup(&filename, &data, None)?;
+    let backup = list_backups().into_iter().find(|b| b.name == filename);
+    
+    prune_snapshots(&load_settings().backup_retention)?;
+    Ok(ScheduledBackupEvent { backup, changed: true })
+}
+
+#[cfg(feature = "gui")]
+fn latest_scheduled_run() -> Option<chrono::NaiveDateTime> {
+    list_backup_files(&get_backup_dir()).iter()
+        .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+        .filter(|name| name.starts_with(SCHEDULED_PREFIX))
+        .filter_map(|name| chrono::NaiveDateTime::parse_from_str(&backup_timestamp_from_name(&name), "%Y-%m-%dT%H-%M-%S%.f").ok())
+        .max()
+}
+
+// Checks once a minute whether a scheduled backup is due. Settings are re-read on
+// every tick, so changes to the schedule apply without restarting the app.
+#[cfg(feature = "gui")]
+fn start_backup_scheduler(app: tauri::AppHandle) {
+    use tauri::Emitter;
+    
+    std::thread::spawn(move || {
+        let mut last_run = latest_scheduled_run();
+        loop {
+            std::thread::sleep(std::time::Duration::from_secs(60));
+            
+            let schedule = load_settings().scheduled_backup;
+            if !schedule.enabled {
+                continue;
+            }
+            let now = chrono::Local::now().naive_local();
+            let interval = chrono::Duration::minutes(schedule.interval_minutes.max(1));
+            if last_run.map(|t| now - t < interval).unwrap_or(false) {
+                continue;
+            }
+            last_run = Some(now);
+            
+            match run_scheduled_backup() {
+                Ok(event) => {
+                    if let Err(e) = app.emit("scheduled-backup", event) {
+                        log::warn!("Failed to emit scheduled-backup event: {}", e);
+                    }
+                }
+                Err(e) => log::warn!("Scheduled backup failed: {}", e),
+            }
+        }
+    });
+}
+
+// ===== Encryption =====
+
+const BACKUP_CIPHER: &str = "xchacha20poly1305";
+
+fn derive_key(passphrase: &str, kdf: &KdfParams) -> Result<[u8; 32], String> {
+    use base64::Engine;
+    
+    if kdf.algorithm != "argon2id" {
+        return Err(format!("Unsupported key derivation: {}", kdf.algorithm));
+    }
+    
+    let salt = base64::engine::general_purpose::STANDARD.decode(&kdf.salt).map_err(|e| e.to_string())?;
+    let params = argon2::Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, Some(32))
+        .map_err(|e| e.to_string())?;
+    let argon = argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+    
+    let mut key = [0u8; 32];
+    argon.hash_password_into(passphrase.as_bytes(), &salt, &mut key).map_err(|e| e.to_string())?;
+    Ok(key)
+}
+
+fn encrypt_backup(data: &BackupData, passphrase: &str) -> Result<EncryptedBundle, String> {
+    use base64::Engine;
+    use chacha20poly1305::aead::rand_core::RngCore;
+    use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+    use chacha20poly1305::XChaCha20Poly1305;
+    
+    if passphrase.is_empty() {
+        return Err("Passphrase cannot be empty".to_string());
+    }
+    
+    let b64 = base64::engine::general_purpose::STANDARD;
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let kdf = KdfParams {
+        algorithm: "argon2id".to_string(),
+        salt: b64.encode(salt),
+        memory_kib: argon2::Params::DEFAULT_M_COST,
+        iterations: argon2::Params::DEFAULT_T_COST,
+        parallelism: argon2::Params::DEFAULT_P_COST,
+    };
+    
+    let key = derive_key(passphrase, &kdf)?;
+    let cipher = XChaCha20Poly1305::new(&key.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let plaintext = serde_json::to_vec(data).map_err(|e| e.to_string())?;
+    let ciphertext = cipher.encrypt(&nonce, plaintext.as_slice())
+        .map_err(|_| "Failed to encrypt backup".to_string())?;
+    
+    Ok(EncryptedBundle {
+        version: data.version,
+        timestamp: data.timestamp.clone(),
+        metadata: data.metadata.clone(),
+        cipher: BACKUP_CIPHER.to_string(),
+        kdf,
+        nonce: b64.encode(nonce),
+        ciphertext: b64.encode(ciphertext),
+    })
+}
+
+fn decrypt_backup(bundle: &EncryptedBundle, passphrase: &str) -> Result<BackupData, String> {
+    use base64::Engine;
+    use chacha20poly1305::aead::{Aead, KeyInit};
+    use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+    
+    if bundle.cipher != BACKUP_CIPHER {
+        return Err(format!("Unsupported cipher: {}", bundle.cipher));
+    }
+    
+    let b64 = base64::engine::general_purpose::STANDARD;
+    let nonce = b64.decode(&bundle.nonce).map_err(|e| e.to_string())?;
+    if nonce.len() != 24 {
+        return Err("Invalid nonce in encrypted backup".to_string());
+    }
+    let ciphertext = b64.decode(&bundle.ciphertext).map_err(|e| e.to_string())?;
+    
+    let key = derive_key(passphrase, &bundle.kdf)?;
+    let cipher = XChaCha20Poly1305::new(&key.into());
+    let plaintext = cipher.decrypt(XNonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| "Wrong passphrase or corrupted backup".to_string())?;
+    
+    serde_json::from_slice(&plaintext).map_err(|e| e.to_string())
+}
+
+// ===== Configuration History =====
+
+// Every change is committed to a local git repository holding one normalized JSON file
+// per server at `<tool>/<server>.json`, so `git log -- <file>` is that server's history.
+// Recording is best effort: a missing `git` binary never blocks a config change.
+
+fn get_history_dir() -> PathBuf {
+    get_home_dir().join(".mcp-manager").join("history")
+}
+
+fn run_git(args: &[&str]) -> Result<String, String> {
+    let output = std::process::Command::new("git")
+        .args(["-c", "user.name=MCP Manager", "-c", "user.email=mcp-manager@localhost", "-c", "commit.gpgsign=false"])
+        .args(args)
+        .current_dir(get_history_dir())
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+// Tool and server names become path components, so anything beyond [A-Za-z0-9_-]
+// is percent-encoded
+fn encode_history_name(name: &str) -> String {
+    name.bytes().map(|b| {
+        if b.is_ascii_alphanumeric() || b == b'-' || b == b'_' {
+            (b as char).to_string()
+        } else {
+            format!("%{:02X}", b)
+        }
+    }).collect()
+}
+
+fn decode_history_name(encoded: &str) -> String {
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes.get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(b) => {
+                decoded.push(b);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+fn write_history_tool(tool: &str) -> Result<(), String> {
+    let tool_dir = get_history_dir().join(encode_history_name(tool));
+    if tool_dir.exists() {
+        fs::remove_dir_all(&tool_dir).map_err(|e| e.to_string())?;
+    }
+    fs::create_dir_all(&tool_dir).map_err(|e| e.to_string())?;
+    // Keeps the directory in git when the tool has no servers
+    fs::write(tool_dir.join(".keep"), "").map_err(|e| e.to_string())?;
+    
+    for server in read_servers(tool) {
+        let value = serde_json::to_value(&server).map_err(|e| e.to_string())?;
+        let content = serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?;
+        fs::write(tool_dir.join(format!("{}.json", encode_history_name(&server.name))), content + "\n")
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn commit_history(message: &str) -> Result<(), String> {
+    run_git(&["add", "-A"])?;
+    if run_git(&["status", "--porcelain"])?.trim().is_empty() {
+        return Ok(());
+    }
+    run_git(&["commit", "-q", "-m", message]).map(|_| ())
+}
+
+fn ensure_history_repo() -> Result<(), String> {
+    let history_dir = get_history_dir();
+    if history_dir.join(".git").exists() {
+        return Ok(());
+    }
+    
+    fs::create_dir_all(&history_dir).map_err(|e| e.to_string())?;
+    run_git(&["init", "-q"])?;
+    for tool in get_all_configs_internal().keys() {
+        write_history_tool(tool)?;
+    }
+    commit_history("Initial configuration")
+}
+
+// Run before a write so the command's commit holds only its own change; edits made to
+// the files outside MCP Manager since the last commit are committed separately first
+fn try_prepare_history<S: AsRef<str>>(tools: &[S]) -> Result<(), String> {
+    ensure_history_repo()?;
+    for tool in tools {
+        write_history_tool(tool.as_ref())?;
+    }
+    commit_history("External changes")
+}
+
+fn prepare_history<S: AsRef<str>>(tools: &[S]) {
+    if let Err(e) = try_prepare_history(tools) {
+        log::warn!("Failed to prepare history: {}", e);
+    }
+}
+
+fn try_record_history(command: &str, changes: &[(String, Vec<String>)]) -> Result<(), String> {
+    ensure_history_repo()?;
+    for (tool, _) in changes {
+        write_history_tool(tool)?;
+    }
+    
+    let summary: Vec<String> = changes.iter().map(|(tool, servers)| {
+        if servers.is_empty() {
+            tool.clone()
+        } else {
+            format!("{} ({})", tool, servers.join(", "))
+        }
+    }).collect();
+    commit_history(&format!("{}: {}", command, summary.join("; ")))
+}
+
+// Called after a mutating command has written its changes; `changes` lists the
+// affected servers per tool for the commit message
+fn record_history(command: &str, changes: &[(String, Vec<String>)]) {
+    if let Err(e) = try_record_history(command, changes) {
+        log::warn!("Failed to record history for {}: {}", command, e);
+    }
+}
+
+fn parse_history_log(output: &str) -> Vec<HistoryEntry> {
+    output.lines().filter_map(|line| {
+        let mut parts = line.splitn(3, '\x1f');
+        Some(HistoryEntry {
+            commit: parts.next()?.to_string(),
+            timestamp: parts.next()?.to_string(),
+            message: parts.next()?.to_string(),
+        })
+    }).collect()
+}
+
+fn history_log(limit: Option<usize>, path: Option<&str>) -> Result<Vec<HistoryEntry>, String> {
+    if !get_history_dir().join(".git").exists() {
+        return Ok(vec![]);
+    }
+    
+    let limit = format!("-n{}", limit.unwrap_or(100));
+    let mut args = vec!["log", "--format=%H%x1f%cI%x1f%s", limit.as_str()];
+    if let Some(path) = path {
+        args.extend(["--", path]);
+    }
+    Ok(parse_history_log(&run_git(&args)?))
+}
+
+fn read_history_servers(commit: &str) -> Result<HashMap<String, Vec<McpServer>>, String> {
+    let mut tools: HashMap<String, Vec<McpServer>> = HashMap::new();
+    
+    for path in run_git(&["ls-tree", "-r", "--name-only", commit])?.lines() {
+        let (tool_dir, file) = match path.split_once('/') {
+            Some(parts) => parts,
+            None => continue,
+        };
+        let servers = tools.entry(decode_history_name(tool_dir)).or_default();
+        if file.ends_with(".json") {
+            let content = run_git(&["show", &format!("{}:{}", commit, path)])?;
+            servers.push(serde_json::from_str(&content).map_err(|e| e.to_string())?);
+        }
+    }
+    Ok(tools)
+}
+
+// ===== Change Tracking =====
+
+// Server lists of the tools a mutating command is about to write
+struct PendingChange {
+    before: Vec<(String, Vec<McpServer>)>,
+}
+
+// Every mutating command calls this before writing and `finish_change` afterwards
+fn begin_change<S: AsRef<str>>(tools: &[S], trigger: &str) -> Result<PendingChange, String> {
+    snapshot_before_write(tools, trigger)?;
+    Ok(PendingChange {
+        before: tools.iter().map(|t| (t.as_ref().to_string(), read_servers(t.as_ref()))).collect(),
+    })
+}
+
+fn finish_change(command: &str, pending: PendingChange, changes: &[(String, Vec<String>)]) {
+    record_history(command, changes);
+    if let Err(e) = record_journal(command, pending) {
+        log::warn!("Failed to record {} in the undo journal: {}", command, e);
+    }
+}
+
+// ===== Undo Journal =====
+
+const JOURNAL_LIMIT: usize = 100;
+
+fn get_journal_path() -> PathBuf {
+    get_home_dir().join(".mcp-manager").join("journal.json")
+}
+
+fn load_journal() -> Journal {
+    fs::read_to_string(get_journal_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_journal(journal: &Journal) -> Result<(), String> {
+    let path = get_journal_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(journal).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+fn record_journal(command: &str, pending: PendingChange) -> Result<(), String> {
+    let changes: Vec<JournalChange> = pending.before.into_iter()
+        .map(|(tool, before)| JournalChange { after: read_servers(&tool), tool, before })
+        .filter(|change| !same_servers(&change.before, &change.after))
+        .collect();
+    if changes.is_empty() {
+        return Ok(());
+    }
+    
+    let mut journal = load_journal();
+    // A new change makes everything that was undone unreachable
+    journal.entries.truncate(journal.cursor);
+    journal.entries.push(JournalEntry {
+        timestamp: chrono::Local::now().format("%Y-%m-%dT%H-%M-%S").to_string(),
+        command: command.to_string(),
+        changes,
+    });
+    if journal.entries.len() > JOURNAL_LIMIT {
+        let excess = journal.entries.len() - JOURNAL_LIMIT;
+        journal.entries.drain(..excess);
+    }
+    journal.cursor = journal.entries.len();
+    save_journal(&journal)
+}
+
+// Order-insensitive comparison, since tools may list servers in any order
+fn same_servers(a: &[McpServer], b: &[McpServer]) -> bool {
+    let normalize = |servers: &[McpServer]| {
+        let mut values: Vec<serde_json::Value> = servers.iter()
+            .filter_map(|s| serde_json::to_value(s).ok())
+            .collect();
+        values.sort_by_key(|v| v["name"].as_str().unwrap_or("").to_string());
+        values
+    };
+    normalize(a) == normalize(b)
+}
+
+// Writes one side of a journal entry, after checking that every tool is still in the
+// state the other side left it in. Anything else means the config was changed since,
+// and applying the entry would silently discard that change.
+fn apply_journal_entry(entry: &JournalEntry, undo: bool, force: bool) -> Result<(), String> {
+    if !force {
+        let conflicts: Vec<&str> = entry.changes.iter()
+            .filter(|change| {
+                let expected = if undo { &change.after } else { &change.before };
+                !same_servers(&read_servers(&change.tool), expected)
+            })
+            .map(|change| change.tool.as_str())
+            .collect();
+        if !conflicts.is_empty() {
+            return Err(format!("Conflict: {} changed since {}", conflicts.join(", "), entry.command));
+        }
+    }
+    
+    let tools: Vec<&str> = entry.changes.iter().map(|c| c.tool.as_str()).collect();
+    snapshot_before_write(&tools, "pre-write")?;
+    
+    let mut changes = vec![];
+    for change in &entry.changes {
+        let servers = if undo { &change.before } else { &change.after };
+        write_servers(&change.tool, servers)?;
+        changes.push((change.tool.clone(), servers.iter().map(|s| s.name.clone()).collect()));
+    }
+    
+    let action = if undo { "undo" } else { "redo" };
+    record_history(&format!("{} {}", action, entry.command), &changes);
+    Ok(())
+}
+
+fn summarize_journal_entry(entry: &JournalEntry, undone: bool) -> JournalSummary {
+    JournalSummary {
+        timestamp: entry.timestamp.clone(),
+        command: entry.command.clone(),
+        tools: entry.changes.iter().map(|c| c.tool.clone()).collect(),
+        undone,
+    }
+}
+
+// ===== Diffing =====
+
+// Flattens a server into comparable fields, with one `env.<KEY>` field per variable
+fn server_fields(server: &McpServer) -> std::collections::BTreeMap<String, serde_json::Value> {
+    let mut fields = std::collections::BTreeMap::new();
+    fields.insert("type".to_string(), serde_json::json!(server.server_type));
+    fields.insert("command".to_string(), serde_json::json!(server.command));
+    fields.insert("args".to_string(), serde_json::json!(server.args));
+    fields.insert("url".to_string(), serde_json::json!(server.url));
+    fields.insert("enabled".to_string(), serde_json::json!(server.enabled));
+    for (key, value) in &server.env {
+        fields.insert(format!("env.{}", key), serde_json::json!(value));
+    }
+    for (name, value) in &server.headers {
+        fields.insert(format!("headers.{}", name), serde_json::json!(value));
+    }
+    fields
+}
+
+fn diff_servers(before: Option<&McpServer>, after: Option<&McpServer>) -> Vec<FieldDiff> {
+    let before_fields = before.map(server_fields).unwrap_or_default();
+    let after_fields = after.map(server_fields).unwrap_or_default();
+    
+    let mut names: Vec<&String> = before_fields.keys().chain(after_fields.keys()).collect();
+    names.sort();
+    names.dedup();
+    
+    names.into_iter().filter_map(|field| {
+        let old = before_fields.get(field).cloned().unwrap_or(serde_json::Value::Null);
+        let new = after_fields.get(field).cloned().unwrap_or(serde_json::Value::Null);
+        if old == new {
+            None
+        } else {
+            Some(FieldDiff { field: field.clone(), before: old, after: new })
+        }
+    }).collect()
+}
+
+fn diff_server_lists(before: &[McpServer], after: &[McpServer]) -> Vec<ServerDiff> {
+    let mut names: Vec<&String> = before.iter().chain(after.iter()).map(|s| &s.name).collect();
+    names.sort();
+    names.dedup();
+    
+    names.into_iter().filter_map(|name| {
+        let old = before.iter().find(|s| &s.name == name);
+        let new = after.iter().find(|s| &s.name == name);
+        let change = match (old, new) {
+            (None, Some(_)) => "added",
+            (Some(_), None) => "removed",
+            _ => "modified",
+        };
+        
+        let fields = diff_servers(old, new);
+        if fields.is_empty() {
+            None
+        } else {
+            Some(ServerDiff { name: name.clone(), change: change.to_string(), fields })
+        }
+    }).collect()
+}
+
+fn diff_configs(
+    before: &HashMap<String, Vec<McpServer>>,
+    after: &HashMap<String, Vec<McpServer>>,
+    tools: &[String],
+) -> Vec<ToolDiff> {
+    let mut tools = tools.to_vec();
+    tools.sort();
+    tools.dedup();
+    
+    tools.into_iter().filter_map(|tool| {
+        let old = before.get(&tool).map(|s| s.as_slice()).unwrap_or(&[]);
+        let new = after.get(&tool).map(|s| s.as_slice()).unwrap_or(&[]);
+        let servers = diff_server_lists(old, new);
+        if servers.is_empty() {
+            None
+        } else {
+            Some(ToolDiff { tool, servers })
+        }
+    }).collect()
+}
+
+// ===== MCP Client =====
+
+const MCP_PROTOCOL_VERSION: &str = "2025-06-18";
+const DEFAULT_CHECK_TIMEOUT_MS: u64 = 10_000;
+// Enough stderr to see why a server failed without keeping a chatty server's whole log
+const STDERR_LIMIT: usize = 64 * 1024;
+
+enum McpTransport {
+    Stdio(StdioTransport),
+    Http(HttpTransport),
+}
+
+struct StdioTransport {
+    child: std::process::Child,
+    stdin: Option<std::process::ChildStdin>,
+    messages: std::sync::mpsc::Receiver<String>,
+    stderr: std::sync::Arc<std::sync::Mutex<String>>,
+    stderr_reader: Option<std::thread::JoinHandle<()>>,
+}
+
+impl StdioTransport {
+    fn spawn(server: &McpServer) -> Result<StdioTransport, String> {
+        use std::io::{BufRead, Read};
+        use std::process::{Command, Stdio};
+        
+        if server.command.trim().is_empty() {
+            return Err(format!("Server {} has no command", server.name));
+        }
+        
+        let mut child = Command::new(expand_home(&server.command))
+            .args(&server.args)
+            .envs(&server.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {}", server.command, e))?;
+        
+        // Messages are newline-delimited JSON on stdout
+        let stdout = child.stdout.take().ok_or("Failed to capture server stdout")?;
+        let (sender, messages) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for line in std::io::BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        
+        let mut stderr_pipe = child.stderr.take().ok_or("Failed to capture server stderr")?;
+        let stderr = std::sync::Arc::new(std::sync::Mutex::new(String::new()));
+        let stderr_buffer = stderr.clone();
+        let stderr_reader = std::thread::spawn(move || {
+            let mut chunk = [0u8; 4096];
+            while let Ok(n) = stderr_pipe.read(&mut chunk) {
+                if n == 0 {
+                    break;
+                }
+                if let Ok(mut buffer) = stderr_buffer.lock() {
+                    if buffer.len() < STDERR_LIMIT {
+                        buffer.push_str(&String::from_utf8_lossy(&chunk[..n]));
+                    }
+                }
+            }
+        });
+        
+        Ok(StdioTransport {
+            stdin: child.stdin.take(),
+            child,
+            messages,
+            stderr,
+            stderr_reader: Some(stderr_reader),
+        })
+    }
+    
+    fn send(&mut self, message: &str) -> Result<(), String> {
+        use std::io::Write;
+        let stdin = self.stdin.as_mut().ok_or("Server stdin is closed")?;
+        writeln!(stdin, "{}", message)
+            .and_then(|_| stdin.flush())
+            .map_err(|e| format!("Failed to write to server: {}", e))
+    }
+    
+    fn receive(&mut self, deadline: std::time::Instant) -> Result<String, String> {
+        use std::sync::mpsc::RecvTimeoutError;
+        let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+        match self.messages.recv_timeout(remaining) {
+            Ok(message) => Ok(message),
+            Err(RecvTimeoutError::Timeout) => Err("Timed out waiting for the server".to_string()),
+            // A server can close stdout and keep running, so its exit is only waited for
+            // until the deadline; `shutdown` kills it if it's still there
+            Err(RecvTimeoutError::Disconnected) => loop {
+                match self.child.try_wait() {
+                    Ok(Some(status)) => return Err(format!("Server exited before responding ({})", status)),
+                    Ok(None) if std::time::Instant::now() < deadline => {
+                        std::thread::sleep(std::time::Duration::from_millis(20));
+                    }
+                    _ => return Err("Server closed its output before responding".to_string()),
+                }
+            },
+        }
+    }
+    
+    // Closing stdin asks the server to exit; it gets a moment to do so before being killed
+    fn shutdown(&mut self) {
+        self.stdin.take();
+        let grace = std::time::Instant::now() + std::time::Duration::from_millis(500);
+        while matches!(self.child.try_wait(), Ok(None)) && std::time::Instant::now() < grace {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        
+        // Programs started through a wrapper like npx can leave a child holding stderr
+        // open, so the reader only gets a short while to drain what was written
+        if let Some(reader) = self.stderr_reader.take() {
+            let drain = std::time::Instant::now() + std::time::Duration::from_millis(200);
+            while !reader.is_finished() && std::time::Instant::now() < drain {
+                std::thread::sleep(std::time::Duration::from_millis(10));
+            }
+        }
+    }
+    
+    fn stderr(&self) -> String {
+        self.stderr.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+}
+
+impl Drop for StdioTransport {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+// Streamable HTTP, falling back to the legacy HTTP+SSE transport when the server
+// rejects the first POST the way pre-2025 servers do
+struct HttpTransport {
+    agent: ureq::Agent,
+    url: String,
+    // Where messages are POSTed: the server URL for streamable HTTP, or the endpoint
+    // announced on the event stream for legacy SSE
+    endpoint: String,
+    legacy_sse: bool,
+    session_id: Option<String>,
+    protocol_version: Option<String>,
+    sender: std::sync::mpsc::Sender<String>,
+    messages: std::sync::mpsc::Receiver<String>,
+    last_status: Option<u16>,
+    headers: Vec<(String, String)>,
+    // Set on shutdown so the legacy SSE reader lets go of the event stream
+    closed: std::sync::Arc<std::sync::atomic::AtomicBool>,
+}
+
+// How often the legacy SSE reader wakes up on a quiet stream to see whether the
+// transport has been shut down
+const EVENT_STREAM_POLL: std::time::Duration = std::time::Duration::from_secs(1);
+
+// Reads the event stream, sitting out read timeouts until the transport is shut down,
+// which ends the stream and closes its connection
+struct EventStreamReader<R> {
+    inner: R,
+    closed: std::sync::Arc<std::sync::atomic::AtomicBool>,
+}
+
+impl<R: std::io::Read> std::io::Read for EventStreamReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        use std::io::ErrorKind;
+        loop {
+            if self.closed.load(std::sync::atomic::Ordering::SeqCst) {
+                return Ok(0);
+            }
+            match self.inner.read(buf) {
+                Err(e) if matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock) => continue,
+                result => return result,
+            }
+        }
+    }
+}
+
+impl HttpTransport {
+    fn connect(url: &str, headers: &HashMap<String, String>) -> Result<HttpTransport, String> {
+        url::Url::parse(url).map_err(|e| format!("Invalid server URL {}: {}", url, e))?;
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(std::time::Duration::from_secs(10))
+            .build();
+        let (sender, messages) = std::sync::mpsc::channel();
+        Ok(HttpTransport {
+            agent,
+            url: url.to_string(),
+            endpoint: url.to_string(),
+            legacy_sse: false,
+            session_id: None,
+            protocol_version: None,
+            sender,
+            messages,
+            last_status: None,
+            headers: headers.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+            closed: Default::default(),
+        })
+    }
+    
+    fn name(&self) -> &'static str {
+        if self.legacy_sse { "sse" } else { "streamable-http" }
+    }
+    
+    // The server's configured headers go on every request, before the protocol ones
+    fn with_headers(&self, mut request: ureq::Request) -> ureq::Request {
+        for (name, value) in &self.headers {
+            request = request.set(name, value);
+        }
+        request
+    }
+    
+    fn send(&mut self, message: &str, deadline: std::time::Instant) -> Result<(), String> {
+        match self.post(message, deadline) {
+            // 400, 404 and 405 on the very first request are how servers that only
+            // speak the old transport answer a streamable HTTP POST
+            Err(_) if !self.legacy_sse && self.session_id.is_none() && matches!(self.last_status, Some(400 | 404 | 405)) => {
+                self.open_event_stream(deadline)?;
+                self.post(message, deadline)
+            }
+            result => result,
+        }
+    }
+    
+    fn post(&mut self, message: &str, deadline: std::time::Instant) -> Result<(), String> {
+        let mut request = self.with_headers(self.agent.post(&self.endpoint))
+            .timeout(deadline.saturating_duration_since(std::time::Instant::now()))
+            .set("Content-Type", "application/json")
+            .set("Accept", "application/json, text/event-stream");
+        if let Some(ref session_id) = self.session_id {
+            request = request.set("Mcp-Session-Id", session_id);
+        }
+        if let Some(ref version) = self.protocol_version {
+            request = request.set("MCP-Protocol-Version", version);
+        }
+        
+        let response = match request.send_string(message) {
+            Ok(response) => response,
+            Err(ureq::Error::Status(code, response)) => {
+                self.last_status = Some(code);
+                return Err(format!("HTTP {} {}", code, response.status_text()));
+            }
+            Err(e) => return Err(e.to_string()),
+        };
+        self.last_status = Some(response.status());
+        if let Some(session_id) = response.header("mcp-session-id") {
+            self.session_id = Some(session_id.to_string());
+        }
+        
+        // Legacy SSE servers answer on the event stream, not in the POST response
+        if self.legacy_sse || response.status() == 202 {
+            return Ok(());
+        }
+        
+        let content_type = response.content_type().to_string();
+        if content_type == "text/event-stream" {
+            let sender = self.sender.clone();
+            let reader = std::io::BufReader::new(response.into_reader());
+            std::thread::spawn(move || {
+                read_sse_events(reader, |event, data| event != "message" || sender.send(data).is_ok());
+            });
+        } else {
+            let body = response.into_string().map_err(|e| e.to_string())?;
+            match serde_json::from_str::<serde_json::Value>(&body) {
+                Ok(serde_json::Value::Array(batch)) => {
+                    for message in batch {
+                        let _ = self.sender.send(message.to_string());
+                    }
+                }
+                Ok(_) => {
+                    let _ = self.sender.send(body);
+                }
+                Err(_) if body.trim().is_empty() => {}
+                Err(e) => return Err(format!("Invalid response from server: {}", e)),
+            }
+        }
+        Ok(())
+    }
+    
+    // Legacy SSE: a GET opens the event stream, whose first event names the URL to POST to.
+    // The stream stays open for the whole session, so it gets a read timeout instead of
+    // an overall one.
+    fn open_event_stream(&mut self, deadline: std::time::Instant) -> Result<(), String> {
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(std::time::Duration::from_secs(10))
+            .timeout_read(EVENT_STREAM_POLL)
+            .build();
+        let response = match self.with_headers(agent.get(&self.url)).set("Accept", "text/event-stream").call() {
+            Ok(response) => response,
+            Err(ureq::Error::Status(code, response)) => {
+                self.last_status = Some(code);
+                return Err(format!("HTTP {} {}", code, response.status_text()));
+            }
+            Err(e) => return Err(e.to_string()),
+        };
+        self.last_status = Some(response.status());
+        
+        let (endpoint_sender, endpoint) = std::sync::mpsc::channel();
+        let sender = self.sender.clone();
+        let reader = std::io::BufReader::new(EventStreamReader {
+            inner: response.into_reader(),
+            closed: self.closed.clone(),
+        });
+        std::thread::spawn(move || {
+            read_sse_events(reader, |event, data| match event {
+                "endpoint" => endpoint_sender.send(data).is_ok(),
+                "message" => sender.send(data).is_ok(),
+                _ => true,
+            });
+        });
+        
+        let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+        let announced = endpoint.recv_timeout(remaining)
+            .map_err(|_| "Server did not announce an SSE message endpoint".to_string())?;
+        let base = url::Url::parse(&self.url).map_err(|e| e.to_string())?;
+        self.endpoint = base.join(announced.trim()).map_err(|e| e.to_string())?.to_string();
+        self.legacy_sse = true;
+        Ok(())
+    }
+    
+    fn receive(&mut self, deadline: std::time::Instant) -> Result<String, String> {
+        let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+        self.messages.recv_timeout(remaining)
+            .map_err(|_| "Timed out waiting for the server".to_string())
+    }
+    
+    // Streamable HTTP sessions are ended explicitly; servers may not support it.
+    // Legacy SSE sessions end when the event stream is closed.
+    fn shutdown(&mut self) {
+        self.closed.store(true, std::sync::atomic::Ordering::SeqCst);
+        if let Some(session_id) = self.session_id.take() {
+            if !self.legacy_sse {
+                let _ = self.with_headers(self.agent.delete(&self.url))
+                    .timeout(std::time::Duration::from_secs(2))
+                    .set("Mcp-Session-Id", &session_id)
+                    .call();
+            }
+        }
+    }
+}
+
+impl Drop for HttpTransport {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+// Calls `on_event(event, data)` for every event on a `text/event-stream` body until the
+// stream ends or the callback returns false
+fn read_sse_events<R: std::io::BufRead>(reader: R, mut on_event: impl FnMut(&str, String) -> bool) {
+    let mut event = String::new();
+    let mut data: Vec<String> = vec![];
+    for line in reader.lines().map_while(Result::ok) {
+        if line.is_empty() {
+            if !data.is_empty() {
+                let name = if event.is_empty() { "message" } else { event.as_str() };
+                if !on_event(name, data.join("\n")) {
+                    return;
+                }
+            }
+            event.clear();
+            data.clear();
+        } else if let Some(value) = line.strip_prefix("event:") {
+            event = value.trim().to_string();
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data.push(value.strip_prefix(' ').unwrap_or(value).to_string());
+        }
+    }
+}
+
+// Servers with a URL are reached over HTTP unless explicitly configured as stdio
+fn uses_http(server: &McpServer) -> bool {
+    server.server_type != "stdio" && !server.url.is_empty()
+}
+
+struct McpClient {
+    transport: McpTransport,
+    next_id: u64,
+    // Messages since the last `take_exchange`, for the inspector
+    exchange: Vec<RpcMessage>,
+}
+
+impl McpClient {
+    // Servers being recorded are started directly: only the AI tool's sessions are recorded
+    fn connect(server: &McpServer) -> Result<McpClient, String> {
+        let transport = if uses_http(server) {
+            McpTransport::Http(HttpTransport::connect(&server.url, &server.headers)?)
+        } else {
+            McpTransport::Stdio(StdioTransport::spawn(&unwrap_recording(server))?)
+        };
+        Ok(McpClient { transport, next_id: 1, exchange: vec![] })
+    }
+    
+    fn transport_name(&self) -> &'static str {
+        match self.transport {
+            McpTransport::Stdio(_) => "stdio",
+            McpTransport::Http(ref http) => http.name(),
+        }
+    }
+    
+    fn exited(&mut self) -> bool {
+        match self.transport {
+            McpTransport::Stdio(ref mut stdio) => matches!(stdio.child.try_wait(), Ok(Some(_))),
+            McpTransport::Http(_) => false,
+        }
+    }
+    
+    fn http_status(&self) -> Option<u16> {
+        match self.transport {
+            McpTransport::Stdio(_) => None,
+            McpTransport::Http(ref http) => http.last_status,
+        }
+    }
+    
+    fn send(&mut self, message: &serde_json::Value, deadline: std::time::Instant) -> Result<(), String> {
+        self.exchange.push(RpcMessage { direction: "sent".to_string(), message: message.clone() });
+        match self.transport {
+            McpTransport::Stdio(ref mut stdio) => stdio.send(&message.to_string()),
+            McpTransport::Http(ref mut http) => http.send(&message.to_string(), deadline),
+        }
+    }
+    
+    fn receive(&mut self, deadline: std::time::Instant) -> Result<String, String> {
+        match self.transport {
+            McpTransport::Stdio(ref mut stdio) => stdio.receive(deadline),
+            McpTransport::Http(ref mut http) => http.receive(deadline),
+        }
+    }
+    
+    fn request(&mut self, method: &str, params: serde_json::Value, deadline: std::time::Instant) -> Result<serde_json::Value, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(&serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }), deadline)
+            .map_err(|e| format!("{}: {}", method, e))?;
+        
+        let response = self.wait_for_response(&serde_json::json!(id), deadline)
+            .map_err(|e| format!("{}: {}", method, e))?;
+        if let Some(error) = response.get("error") {
+            let text = error.get("message").and_then(|m| m.as_str()).unwrap_or("unknown error");
+            return Err(format!("{} failed: {}", method, text));
+        }
+        Ok(response.get("result").cloned().unwrap_or(serde_json::Value::Null))
+    }
+    
+    // Sends a message exactly as given and, if it is a request, waits for the response
+    // carrying its id
+    fn send_raw(&mut self, message: &serde_json::Value, deadline: std::time::Instant) -> Result<Option<serde_json::Value>, String> {
+        self.send(message, deadline)?;
+        match message.get("id") {
+            Some(id) if message.get("method").is_some() => self.wait_for_response(id, deadline).map(Some),
+            _ => Ok(None),
+        }
+    }
+    
+    // Returns the whole response message, answering requests from the server meanwhile
+    fn wait_for_response(&mut self, id: &serde_json::Value, deadline: std::time::Instant) -> Result<serde_json::Value, String> {
+        loop {
+            let raw = self.receive(deadline)?;
+            // Servers sometimes log to stdout; anything that isn't JSON-RPC is skipped
+            let message: serde_json::Value = match serde_json::from_str(&raw) {
+                Ok(message) => message,
+                Err(_) => continue,
+            };
+            self.exchange.push(RpcMessage { direction: "received".to_string(), message: message.clone() });
+            
+            if let Some(server_method) = message.get("method").and_then(|m| m.as_str()) {
+                // Requests from the server need an answer or it may stall; notifications don't
+                if let Some(request_id) = message.get("id") {
+                    let reply = match server_method {
+                        "ping" => serde_json::json!({ "jsonrpc": "2.0", "id": request_id, "result": {} }),
+                        _ => serde_json::json!({
+                            "jsonrpc": "2.0",
+                            "id": request_id,
+                            "error": { "code": -32601, "message": "Method not found" },
+                        }),
+                    };
+                    self.send(&reply, deadline)?;
+                }
+                continue;
+            }
+            
+            if message.get("id") == Some(id) {
+                return Ok(message);
+            }
+        }
+    }
+    
+    fn notify(&mut self, method: &str, deadline: std::time::Instant) -> Result<(), String> {
+        self.send(&serde_json::json!({ "jsonrpc": "2.0", "method": method }), deadline)
+    }
+    
+    fn initialize(&mut self, deadline: std::time::Instant) -> Result<serde_json::Value, String> {
+        let params = serde_json::json!({
+            "protocolVersion": MCP_PROTOCOL_VERSION,
+            "capabilities": {},
+            "clientInfo": { "name": "mcp-manager", "version": env!("CARGO_PKG_VERSION") },
+        });
+        let result = self.request("initialize", params, deadline)?;
+        if let McpTransport::Http(ref mut http) = self.transport {
+            http.protocol_version = result.get("protocolVersion").and_then(|v| v.as_str()).map(|v| v.to_string());
+        }
+        self.notify("notifications/initialized", deadline)?;
+        Ok(result)
+    }
+    
+    // Follows `nextCursor` until every page of a `*/list` method has been read
+    fn list_all(&mut self, method: &str, key: &str, deadline: std::time::Instant) -> Result<Vec<serde_json::Value>, String> {
+        let mut items = vec![];
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match cursor {
+                Some(ref cursor) => serde_json::json!({ "cursor": cursor }),
+                None => serde_json::json!({}),
+            };
+            let result = self.request(method, params, deadline)?;
+            if let Some(page) = result.get(key).and_then(|v| v.as_array()) {
+                items.extend(page.iter().cloned());
+            }
+            cursor = result.get("nextCursor").and_then(|c| c.as_str()).map(|c| c.to_string());
+            if cursor.is_none() {
+                return Ok(items);
+            }
+        }
+    }
+    
+    fn take_exchange(&mut self) -> Vec<RpcMessage> {
+        std::mem::take(&mut self.exchange)
+    }
+    
+    fn stderr(&self) -> String {
+        match self.transport {
+            McpTransport::Stdio(ref stdio) => stdio.stderr(),
+            McpTransport::Http(_) => String::new(),
+        }
+    }
+    
+    // Stops the server, or ends the HTTP session, and returns what it wrote to stderr
+    fn close(mut self) -> String {
+        match self.transport {
+            McpTransport::Stdio(ref mut stdio) => stdio.shutdown(),
+            McpTransport::Http(ref mut http) => http.shutdown(),
+        }
+        self.stderr()
+    }
+}
+
+// ===== Health Checks =====
+
+fn check_server_health(tool: &str, server: &McpServer, timeout: std::time::Duration) -> HealthCheck {
+    let check = run_health_check(server, timeout);
+    if let Err(e) = record_health(tool, server, &check) {
+        log::warn!("Failed to record health check of {}: {}", server.name, e);
+    }
+    check
+}
+
+fn run_health_check(server: &McpServer, timeout: std::time::Duration) -> HealthCheck {
+    let started = std::time::Instant::now();
+    let deadline = started + timeout;
+    let mut check = HealthCheck {
+        server: server.name.clone(),
+        status: "error".to_string(),
+        // Corrected once connected, since HTTP may fall back to legacy SSE
+        transport: if uses_http(server) { "streamable-http" } else { "stdio" }.to_string(),
+        http_status: None,
+        latency_ms: None,
+        startup_ms: None,
+        protocol_version: None,
+        server_info: None,
+        tool_count: None,
+        error: None,
+        error_class: None,
+        stderr: String::new(),
+        duration_ms: 0,
+    };
+    
+    match McpClient::connect(server) {
+        Ok(mut client) => {
+            let connected = started.elapsed();
+            match probe_server(&mut client, deadline) {
+                Ok((init, latency, tool_count)) => {
+                    check.status = "ok".to_string();
+                    check.latency_ms = Some(latency.as_millis() as u64);
+                    check.startup_ms = Some((connected + latency).as_millis() as u64);
+                    check.protocol_version = init.get("protocolVersion").and_then(|v| v.as_str()).map(|v| v.to_string());
+                    check.server_info = init.get("serverInfo").cloned();
+                    check.tool_count = Some(tool_count);
+                }
+                Err(e) => {
+                    let class = classify_failure(&mut client, deadline);
+                    if class == "unauthorized" || class == "timeout" {
+                        check.status = class.to_string();
+                    }
+                    check.error_class = Some(class.to_string());
+                    check.error = Some(e);
+                }
+            }
+            check.transport = client.transport_name().to_string();
+            check.http_status = client.http_status();
+            check.stderr = client.close();
+        }
+        Err(e) => {
+            check.error_class = Some(if uses_http(server) { "connection" } else { "spawn" }.to_string());
+            check.error = Some(e);
+        }
+    }
+    
+    check.duration_ms = started.elapsed().as_millis() as u64;
+    check
+}
+
+fn classify_failure(client: &mut McpClient, deadline: std::time::Instant) -> &'static str {
+    match client.http_status() {
+        Some(401 | 403) => "unauthorized",
+        Some(code) if code >= 400 => "http",
+        _ if client.exited() => "exited",
+        _ if std::time::Instant::now() >= deadline => "timeout",
+        None if client.transport_name() != "stdio" => "connection",
+        _ => "protocol",
+    }
+}
+
+// Returns the `initialize` result, how long it took, and the number of tools
+fn probe_server(client: &mut McpClient, deadline: std::time::Instant) -> Result<(serde_json::Value, std::time::Duration, usize), String> {
+    let started = std::time::Instant::now();
+    let init = client.initialize(deadline)?;
+    let latency = started.elapsed();
+    // Servers that don't declare the tools capability may not implement `tools/list`
+    if init.pointer("/capabilities/tools").is_none() {
+        return Ok((init, latency, 0));
+    }
+    let tools = client.list_all("tools/list", "tools", deadline)?;
+    Ok((init, latency, tools.len()))
+}
+
+// ===== Transcripts =====
+
+// First argument that turns the app binary into the recording shim (see main.rs)
+pub const RECORD_FLAG: &str = "--record-mcp";
+
+fn get_transcripts_dir(tool: &str, server: &str) -> PathBuf {
+    get_home_dir().join(".mcp-manager").join("transcripts")
+        .join(encode_history_name(tool))
+        .join(encode_history_name(server))
+}
+
+// The original command and args of a server wrapped by `wrap_for_recording`
+fn recorded_command(server: &McpServer) -> Option<(String, Vec<String>)> {
+    match server.args.as_slice() {
+        [flag, _, _, separator, command, rest @ ..] if flag == RECORD_FLAG && separator == "--" => {
+            Some((command.clone(), rest.to_vec()))
+        }
+        _ => None,
+    }
+}
+
+// The client then starts this app's binary, which starts the real command. The
+// recorded tool and server name are fixed at wrap time.
+fn wrap_for_recording(tool: &str, server: &mut McpServer) -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let mut args = vec![
+        RECORD_FLAG.to_string(),
+        tool.to_string(),
+        server.name.clone(),
+        "--".to_string(),
+        server.command.clone(),
+    ];
+    args.append(&mut server.args);
+    server.command = exe.to_string_lossy().to_string();
+    server.args = args;
+    Ok(())
+}
+
+fn unwrap_recording(server: &McpServer) -> McpServer {
+    let mut unwrapped = server.clone();
+    if let Some((command, args)) = recorded_command(server) {
+        unwrapped.command = command;
+        unwrapped.args = args;
+    }
+    unwrapped
+}
+
+// Runs as the server's command when recording: starts the real command, passes stdin
+// and stdout through line by line, and appends every line to a new transcript. Stderr
+// is inherited. Recording problems never stop the server from running.
+// Args: <tool> <server> -- <command> [args...]
+pub fn run_recording_shim(args: &[String]) -> i32 {
+    use std::io::{BufRead, Write};
+    
+    let (tool, server, command, command_args) = match args {
+        [tool, server, separator, command, rest @ ..] if separator == "--" => (tool, server, command, rest),
+        _ => {
+            eprintln!("usage: {} <tool> <server> -- <command> [args...]", RECORD_FLAG);
+            return 2;
+        }
+    };
+    
+    let mut child = match std::process::Command::new(command)
+        .args(command_args)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("mcp-manager: failed to start {}: {}", command, e);
+            return 127;
+        }
+    };
+    
+    let dir = get_transcripts_dir(tool, server);
+    let filename = format!("{}.jsonl", chrono::Local::now().format("%Y-%m-%dT%H-%M-%S%.3f"));
+    let transcript = fs::create_dir_all(&dir)
+        .and_then(|_| fs::OpenOptions::new().create(true).append(true).open(dir.join(filename)));
+    let transcript = match transcript {
+        Ok(file) => Some(std::sync::Arc::new(std::sync::Mutex::new(file))),
+        Err(e) => {
+            eprintln!("mcp-manager: not recording transcript: {}", e);
+            None
+        }
+    };
+    let record = move |direction: &str, line: &str| {
+        if let Some(ref transcript) = transcript {
+            let entry = TranscriptEntry {
+                timestamp: chrono::Local::now().to_rfc3339(),
+                direction: direction.to_string(),
+                message: serde_json::from_str(line).unwrap_or_else(|_| serde_json::json!(line)),
+            };
+            if let (Ok(mut file), Ok(json)) = (transcript.lock(), serde_json::to_string(&entry)) {
+                let _ = writeln!(file, "{}", json);
+            }
+        }
+    };
+    let record_sent = record.clone();
+    
+    let mut child_stdin = child.stdin.take();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines().map_while(Result::ok) {
+            record_sent("sent", &line);
+            let forwarded = child_stdin.as_mut()
+                .map(|stdin| writeln!(stdin, "{}", line).and_then(|_| stdin.flush()).is_ok())
+                .unwrap_or(false);
+            if !forwarded {
+                break;
+            }
+        }
+        // Dropping the pipe tells the server its client has gone
+        drop(child_stdin);
+    });
+    
+    if let Some(stdout) = child.stdout.take() {
+        let mut out = std::io::stdout();
+        for line in std::io::BufReader::new(stdout).lines().map_while(Result::ok) {
+            record("received", &line);
+            if writeln!(out, "{}", line).and_then(|_| out.flush()).is_err() {
+                break;
+            }
+        }
+    }
+    
+    child.wait().ok().and_then(|status| status.code()).unwrap_or(1)
+}
+
+fn transcript_path(tool: &str, server: &str, name: &str) -> Result<PathBuf, String> {
+    let is_plain_name = !name.starts_with('.') && !name.contains(['/', '\\', '\0']) && name.ends_with(".jsonl");
+    let path = get_transcripts_dir(tool, server).join(name);
+    if !is_plain_name || !path.is_file() {
+        return Err(format!("Transcript not found: {}", name));
+    }
+    Ok(path)
+}
+
+fn read_transcript(path: &std::path::Path) -> Result<Vec<TranscriptEntry>, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    Ok(content.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+}
+
+fn list_transcripts(tool: &str, server: &str) -> Vec<TranscriptInfo> {
+    let mut transcripts: Vec<TranscriptInfo> = fs::read_dir(get_transcripts_dir(tool, server))
+        .map(|entries| {
+            entries.filter_map(|e| e.ok())
+                .filter(|e| e.file_name().to_string_lossy().ends_with(".jsonl"))
+                .map(|e| TranscriptInfo {
+                    name: e.file_name().to_string_lossy().to_string(),
+                    messages: fs::read_to_string(e.path()).map(|c| c.lines().count()).unwrap_or(0),
+                    size: e.metadata().map(|m| m.len()).unwrap_or(0),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    transcripts.sort_by(|a, b| b.name.cmp(&a.name));
+    transcripts
+}
+
+// Sends the client's side of a transcript to a fresh instance of the server and
+// compares each response with the recorded one
+fn replay_transcript(server: &McpServer, entries: &[TranscriptEntry]) -> Result<TranscriptReplay, String> {
+    let mut client = McpClient::connect(server)?;
+    let mut steps = vec![];
+    
+    for entry in entries.iter().filter(|e| e.direction == "sent") {
+        let request = &entry.message;
+        // Answers to the server's own requests depend on that run; the client answers those itself
+        if request.get("method").is_none() {
+            continue;
+        }
+        let recorded = request.get("id").and_then(|id| {
+            entries.iter()
+                .filter(|e| e.direction == "received")
+                .map(|e| &e.message)
+                .find(|m| m.get("id") == Some(id) && m.get("method").is_none())
+                .cloned()
+        });
+        
+        let deadline = std::time::Instant::now() + std::time::Duration::from_millis(INSPECTOR_TIMEOUT_MS);
+        let (replayed, error) = match client.send_raw(request, deadline) {
+            Ok(replayed) => (replayed, None),
+            Err(e) => (None, Some(e)),
+        };
+        let outcome = |m: &Option<serde_json::Value>| m.as_ref().map(|m| (m.get("result").cloned(), m.get("error").cloned()));
+        steps.push(ReplayStep {
+            matches: error.is_none() && outcome(&recorded) == outcome(&replayed),
+            request: request.clone(),
+            recorded,
+            replayed,
+            error,
+        });
+    }
+    
+    client.take_exchange();
+    Ok(TranscriptReplay { steps, stderr: client.close() })
+}
+
+// ===== Health History =====
+
+const HEALTH_HISTORY_LIMIT: usize = 5000;
+
+fn get_health_history_path() -> PathBuf {
+    get_home_dir().join(".mcp-manager").join("health.jsonl")
+}
+
+fn load_health_records() -> Vec<HealthRecord> {
+    fs::read_to_string(get_health_history_path())
+        .map(|content| content.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+        .unwrap_or_default()
+}
+
+// Appends one line per check; the file is cut back to the newest records once it has
+// grown well past the limit, so it isn't rewritten on every check
+fn record_health(tool: &str, server: &McpServer, check: &HealthCheck) -> Result<(), String> {
+    use std::io::Write;
+    
+    let record = HealthRecord {
+        timestamp: chrono::Local::now().to_rfc3339(),
+        tool: tool.to_string(),
+        server: server.name.clone(),
+        fingerprint: server_fingerprint(server),
+        transport: check.transport.clone(),
+        status: check.status.clone(),
+        startup_ms: check.startup_ms,
+        error_class: check.error_class.clone(),
+        server_version: check.server_info.as_ref()
+            .and_then(|info| info.get("version"))
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string()),
+    };
+    
+    let path = get_health_history_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let line = serde_json::to_string(&record).map_err(|e| e.to_string())?;
+    let mut file = fs::OpenOptions::new().create(true).append(true).open(&path).map_err(|e| e.to_string())?;
+    writeln!(file, "{}", line).map_err(|e| e.to_string())?;
+    
+    let records = load_health_records();
+    if records.len() > HEALTH_HISTORY_LIMIT + HEALTH_HISTORY_LIMIT / 10 {
+        let kept: Vec<String> = records[records.len() - HEALTH_HISTORY_LIMIT..].iter()
+            .filter_map(|r| serde_json::to_string(r).ok())
+            .collect();
+        fs::write(&path, kept.join("\n") + "\n").map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn health_timeline(tool: &str, server: &str, limit: usize) -> HealthTimeline {
+    let mut checks: Vec<HealthRecord> = load_health_records().into_iter()
+        .filter(|r| r.tool == tool && r.server == server)
+        .collect();
+    if checks.len() > limit {
+        checks.drain(..checks.len() - limit);
+    }
+    
+    let succeeded = checks.iter().filter(|r| r.status == "ok").count();
+    let success_rate = Some(succeeded as f64 / checks.len() as f64).filter(|_| !checks.is_empty());
+    let mut startups: Vec<u64> = checks.iter().filter_map(|r| r.startup_ms).collect();
+    startups.sort();
+    let median_startup_ms = startups.get(startups.len() / 2).copied();
+    
+    HealthTimeline { tool: tool.to_string(), server: server.to_string(), checks, success_rate, median_startup_ms }
+}
+
+// ===== Capability Inventory =====
+
+fn get_cache_dir() -> PathBuf {
+    get_home_dir().join(".mcp-manager").join("cache")
+}
+
+// Anything that changes how the server is started or reached gets a fresh inventory.
+// Recording a server doesn't change what it is, so it keeps its fingerprint.
+fn server_fingerprint(server: &McpServer) -> String {
+    let server = &unwrap_recording(server);
+    let identity = serde_json::json!({
+        "command": server.command,
+        "args": server.args,
+        "env": server.env,
+        "url": server.url,
+        "headers": server.headers,
+    });
+    hash_bytes(identity.to_string().as_bytes())
+}
+
+fn fetch_inventory(server: &McpServer, timeout: std::time::Duration) -> ServerInventory {
+    let deadline = std::time::Instant::now() + timeout;
+    let mut inventory = ServerInventory {
+        server: server.name.clone(),
+        fingerprint: server_fingerprint(server),
+        fetched_at: chrono::Local::now().to_rfc3339(),
+        tools: vec![],
+        resources: vec![],
+        prompts: vec![],
+        error: None,
+        cached: false,
+    };
+    
+    let result = McpClient::connect(server).and_then(|mut client| {
+        let result = read_capabilities(&mut client, &mut inventory, deadline);
+        client.close();
+        result
+    });
+    if let Err(e) = result {
+        inventory.error = Some(e);
+    }
+    inventory
+}
+
+// Only lists what the server declares in its `initialize` capabilities
+fn read_capabilities(client: &mut McpClient, inventory: &mut ServerInventory, deadline: std::time::Instant) -> Result<(), String> {
+    let init = client.initialize(deadline)?;
+    if init.pointer("/capabilities/tools").is_some() {
+        inventory.tools = client.list_all("tools/list", "tools", deadline)?;
+    }
+    if init.pointer("/capabilities/resources").is_some() {
+        inventory.resources = client.list_all("resources/list", "resources", deadline)?;
+    }
+    if init.pointer("/capabilities/prompts").is_some() {
+        inventory.prompts = client.list_all("prompts/list", "prompts", deadline)?;
+    }
+    Ok(())
+}
+
+fn load_inventory(server: &McpServer, refresh: bool) -> ServerInventory {
+    let path = get_cache_dir().join(format!("{}.json", server_fingerprint(server)));
+    
+    if !refresh {
+        let cached = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<ServerInventory>(&content).ok());
+        if let Some(mut inventory) = cached {
+            // The same command can be configured under different names in different tools
+            inventory.server = server.name.clone();
+            inventory.cached = true;
+            return inventory;
+        }
+    }
+    
+    let inventory = fetch_inventory(server, std::time::Duration::from_millis(DEFAULT_CHECK_TIMEOUT_MS));
+    if inventory.error.is_none() {
+        let written = fs::create_dir_all(get_cache_dir())
+            .map_err(|e| e.to_string())
+            .and_then(|_| serde_json::to_string_pretty(&inventory).map_err(|e| e.to_string()))
+            .and_then(|content| fs::write(&path, content).map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            log::warn!("Failed to cache inventory of {}: {}", server.name, e);
+        }
+    }
+    inventory
+}
+
+// Servers are contacted in parallel, since each can take up to the full timeout
+fn server_inventories(servers: &[McpServer], refresh: bool) -> Vec<ServerInventory> {
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = servers.iter()
+            .map(|server| scope.spawn(move || load_inventory(server, refresh)))
+            .collect();
+        handles.into_iter().filter_map(|h| h.join().ok()).collect()
+    })
+}
+
+// ===== Inspector =====
+
+// Tool calls can legitimately take a while, unlike the handshake
+const INSPECTOR_TIMEOUT_MS: u64 = 60_000;
+
+// Open inspector sessions, kept in Tauri state. Each client has its own lock so a slow
+// call on one session doesn't hold up the others.
+#[cfg(feature = "gui")]
+#[derive(Default)]
+pub struct InspectorSessions {
+    sessions: std::sync::Mutex<HashMap<String, std::sync::Arc<std::sync::Mutex<McpClient>>>>,
+    next_id: std::sync::atomic::AtomicU64,
+}
+
+#[cfg(feature = "gui")]
+impl InspectorSessions {
+    fn open(&self, server: &McpServer) -> Result<InspectorSession, String> {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_millis(DEFAULT_CHECK_TIMEOUT_MS);
+        let mut client = McpClient::connect(server)?;
+        let initialize = client.initialize(deadline)?;
+        
+        let id = self.next_id.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+        let session_id = format!("inspector-{}", id);
+        let exchange = client.take_exchange();
+        self.sessions.lock().map_err(|e| e.to_string())?
+            .insert(session_id.clone(), std::sync::Arc::new(std::sync::Mutex::new(client)));
+        
+        Ok(InspectorSession { session_id, server: server.name.clone(), initialize, exchange })
+    }
+    
+    fn request(&self, session_id: &str, method: &str, params: serde_json::Value, timeout_ms: Option<u64>) -> Result<InspectorResponse, String> {
+        let client = self.sessions.lock().map_err(|e| e.to_string())?
+            .get(session_id)
+            .cloned()
+            .ok_or_else(|| format!("Inspector session not found: {}", session_id))?;
+        let mut client = client.lock().map_err(|e| e.to_string())?;
+        
+        let deadline = std::time::Instant::now() + std::time::Duration::from_millis(timeout_ms.unwrap_or(INSPECTOR_TIMEOUT_MS));
+        let (result, error) = match client.request(method, params, deadline) {
+            Ok(result) => (Some(result), None),
+            Err(e) => (None, Some(e)),
+        };
+        Ok(InspectorResponse { result, error, exchange: client.take_exchange() })
+    }
+    
+    // Returns what a stdio server wrote to stderr during the session
+    fn close(&self, session_id: &str) -> Result<String, String> {
+        let client = self.sessions.lock().map_err(|e| e.to_string())?
+            .remove(session_id)
+            .ok_or_else(|| format!("Inspector session not found: {}", session_id))?;
+        match std::sync::Arc::try_unwrap(client) {
+            Ok(client) => Ok(client.into_inner().unwrap_or_else(|e| e.into_inner()).close()),
+            // A call still running holds the last reference; the client shuts down when it returns
+            Err(_) => Ok(String::new()),
+        }
+    }
+}
+
+// ===== Token Budget =====
+
+const TOP_TOOLS_REPORTED: usize = 10;
+
+fn tool_limit(tool: &str) -> Option<usize> {
+    get_tool_definitions().into_iter().find(|def| def.name == tool).and_then(|def| def.tool_limit)
+}
+
+// What the client puts in the context for one tool: its name, description and input
+// schema as JSON. Around four characters per token is close enough for JSON-heavy text;
+// this is an estimate, not a tokenizer.
+fn estimate_tool_tokens(tool: &serde_json::Value) -> usize {
+    let definition = serde_json::json!({
+        "name": tool.get("name"),
+        "description": tool.get("description"),
+        "inputSchema": tool.get("inputSchema"),
+    });
+    definition.to_string().len().div_ceil(4)
+}
+
+fn token_budget(tool: &str, servers: &[McpServer], inventories: &HashMap<String, ServerInventory>) -> TokenBudget {
+    let mut estimates = vec![];
+    let mut top_tools = vec![];
+    let mut unavailable = vec![];
+    
+    for server in servers.iter().filter(|s| s.enabled) {
+        let inventory = match inventories.get(&server_fingerprint(server)) {
+            Some(inventory) if inventory.error.is_none() => inventory,
+            _ => {
+                unavailable.push(server.name.clone());
+                continue;
+            }
+        };
+        
+        let mut tokens = 0;
+        for definition in &inventory.tools {
+            let tool_tokens = estimate_tool_tokens(definition);
+            tokens += tool_tokens;
+            top_tools.push(ToolTokenEstimate {
+                server: server.name.clone(),
+                name: definition.get("name").and_then(|n| n.as_str()).unwrap_or_default().to_string(),
+                tokens: tool_tokens,
+            });
+        }
+        estimates.push(ServerTokenEstimate {
+            server: server.name.clone(),
+            tokens,
+            tool_count: inventory.tools.len(),
+        });
+    }
+    
+    estimates.sort_by_key(|e| std::cmp::Reverse(e.tokens));
+    top_tools.sort_by_key(|t| std::cmp::Reverse(t.tokens));
+    top_tools.truncate(TOP_TOOLS_REPORTED);
+    
+    let tool_count = estimates.iter().map(|e| e.tool_count).sum();
+    let tool_limit = tool_limit(tool);
+    TokenBudget {
+        tool: tool.to_string(),
+        total_tokens: estimates.iter().map(|e| e.tokens).sum(),
+        tool_count,
+        tool_limit,
+        exceeds_limit: tool_limit.map(|limit| tool_count > limit).unwrap_or(false),
+        servers: estimates,
+        top_tools,
+        unavailable,
+    }
+}
+
+// Inventories of every enabled server across `configs`, keyed by fingerprint. A server
+// configured identically in several tools is only contacted once.
+fn inventories_for(configs: &HashMap<String, Vec<McpServer>>, refresh: bool) -> HashMap<String, ServerInventory> {
+    let mut unique: HashMap<String, McpServer> = HashMap::new();
+    for server in configs.values().flatten().filter(|s| s.enabled) {
+        unique.entry(server_fingerprint(server)).or_insert_with(|| server.clone());
+    }
+    let servers: Vec<McpServer> = unique.into_values().collect();
+    server_inventories(&servers, refresh).into_iter()
+        .map(|inventory| (inventory.fingerprint.clone(), inventory))
+        .collect()
+}
+
+// ===== Tool Collisions =====
+
+// Clients that namespace MCP tools by server (Claude Code exposes `mcp__<server>__<tool>`),
+// so the same tool name on two servers never clashes there
+const NAMESPACED_TOOL_CLIENTS: &[&str] = &["claude"];
+
+// Collisions are reported per AI tool, the only grouping of servers the settings know of
+fn tool_collisions(tool: &str, servers: &[McpServer], inventories: &HashMap<String, ServerInventory>) -> Vec<ToolCollision> {
+    // Tool names per enabled server, in config order
+    let exposed: Vec<(String, Vec<String>)> = servers.iter()
+        .filter(|s| s.enabled)
+        .filter_map(|server| {
+            let inventory = inventories.get(&server_fingerprint(server)).filter(|i| i.error.is_none())?;
+            let names = inventory.tools.iter()
+                .filter_map(|t| t.get("name").and_then(|n| n.as_str()).map(|n| n.to_string()))
+                .collect();
+            Some((server.name.clone(), names))
+        })
+        .collect();
+    
+    let mut providers: Vec<(String, Vec<String>)> = vec![];
+    for (server, names) in &exposed {
+        for name in names {
+            match providers.iter_mut().find(|(n, _)| n == name) {
+                Some((_, list)) if !list.contains(server) => list.push(server.clone()),
+                Some(_) => {}
+                None => providers.push((name.clone(), vec![server.clone()])),
+            }
+        }
+    }
+    
+    providers.into_iter()
+        .filter(|(_, list)| list.len() > 1)
+        .map(|(name, list)| ToolCollision {
+            suggestion: collision_suggestion(tool, &name, &list, &exposed),
+            tool: tool.to_string(),
+            tool_name: name,
+            servers: list,
+        })
+        .collect()
+}
+
+// Disabling a server is only suggested when nothing it exposes would be lost; otherwise
+// the tool is filtered out of all but the first server that provides it
+fn collision_suggestion(tool: &str, name: &str, servers: &[String], exposed: &[(String, Vec<String>)]) -> String {
+    let names_of = |server: &str| exposed.iter().find(|(s, _)| s == server).map(|(_, n)| n.as_slice()).unwrap_or_default();
+    let redundant = servers.iter().rev().find(|server| {
+        names_of(server).iter().all(|n| {
+            exposed.iter().any(|(other, names)| other != *server && names.contains(n))
+        })
+    });
+    if let Some(server) = redundant {
+        return format!("Disable {}: every tool it exposes is also provided by another enabled server", server);
+    }
+    
+    let others = servers[1..].join(", ");
+    match tool {
+        "gemini" => format!("Add \"excludeTools\": [\"{}\"] to {} in the Gemini CLI settings", name, others),
+        _ => format!("Turn off {} for {} in the client's tool settings, or disable one of the servers", name, others),
+    }
+}
+
+// ===== Command Resolution =====
+
+// Runtime a launcher command belongs to, and the binary whose `--version` identifies it
+fn command_runtime(command: &str) -> Option<(&'static str, &'static str)> {
+    let name = std::path::Path::new(command).file_stem()?.to_string_lossy().to_lowercase();
+    match name.as_str() {
+        "npx" | "npm" | "node" | "pnpm" => Some(("node", "node")),
+        "uvx" | "uv" => Some(("uv", "uv")),
+        "python" | "python3" | "pip" | "pipx" => Some(("python", "python3")),
+        "docker" => Some(("docker", "docker")),
+        "bunx" | "bun" => Some(("bun", "bun")),
+        "deno" => Some(("deno", "deno")),
+        _ => None,
+    }
+}
+
+// Tools launched from a GUI (Cursor, VS Code, Claude Desktop) often don't get the
+// PATH set up by the user's shell profile, so the login shell's PATH is read separately
+fn login_shell_path() -> Option<String> {
+    if cfg!(windows) {
+        return None;
+    }
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
+    let output = run_with_timeout(
+        std::process::Command::new(shell).args(["-l", "-c", "printf %s \"$PATH\""]),
+        std::time::Duration::from_secs(5),
+    )?;
+    Some(output).filter(|p| !p.is_empty())
+}
+
+// Runs a command to completion and returns its stdout, killing it if it takes too long
+fn run_with_timeout(command: &mut std::process::Command, timeout: std::time::Duration) -> Option<String> {
+    use std::io::Read;
+    let mut child = command
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .ok()?;
+    
+    let deadline = std::time::Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if std::time::Instant::now() < deadline => std::thread::sleep(std::time::Duration::from_millis(20)),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    }
+    
+    let mut output = String::new();
+    child.stdout.take()?.read_to_string(&mut output).ok()?;
+    Some(output.trim().to_string())
+}
+
+fn is_executable(path: &std::path::Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::metadata(path).map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0).unwrap_or(false)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+// First match for `command` in a PATH-style list. Any match is returned even if it isn't
+// executable, so that case can be told apart from a missing binary.
+fn find_in_path(command: &str, path_var: &str) -> Option<PathBuf> {
+    let extensions: &[&str] = if cfg!(windows) { &["", ".exe", ".cmd", ".bat"] } else { &[""] };
+    let candidates: Vec<PathBuf> = std::env::split_paths(path_var)
+        .flat_map(|dir| extensions.iter().map(move |ext| dir.join(format!("{}{}", command, ext))))
+        .filter(|candidate| candidate.is_file())
+        .collect();
+    candidates.iter().find(|c| is_executable(c)).or(candidates.first()).cloned()
+}
+
+fn check_command(tool: &str, server: &McpServer, shell_path: Option<&str>, versions: &mut HashMap<String, Option<String>>) -> CommandCheck {
+    // A server being recorded still runs its original command, through this app
+    let server = &unwrap_recording(server);
+    let mut check = CommandCheck {
+        tool: tool.to_string(),
+        server: server.name.clone(),
+        command: server.command.clone(),
+        status: "missing".to_string(),
+        resolved_path: None,
+        runtime: None,
+        runtime_version: None,
+        note: None,
+    };
+    
+    let command = expand_home(&server.command);
+    let resolved = if command.components().count() > 1 {
+        Some(command).filter(|p| p.exists())
+    } else {
+        // A PATH set in the server's env is what the client will use to find the command
+        let app_path = server.env.get("PATH").cloned()
+            .or_else(|| std::env::var("PATH").ok())
+            .unwrap_or_default();
+        match find_in_path(&server.command, &app_path) {
+            Some(found) => Some(found),
+            None => {
+                let found = shell_path.and_then(|p| find_in_path(&server.command, p));
+                if found.is_some() {
+                    check.note = Some("Only found on the login shell PATH; tools started from the desktop may not see it".to_string());
+                }
+                found
+            }
+        }
+    };
+    
+    let resolved = match resolved {
+        Some(resolved) => resolved,
+        None => return check,
+    };
+    check.status = if is_executable(&resolved) { "found" } else { "not-executable" }.to_string();
+    check.resolved_path = Some(resolved.to_string_lossy().to_string());
+    
+    if let Some((runtime, binary)) = command_runtime(&server.command) {
+        check.runtime = Some(runtime.to_string());
+        // The runtime is looked up next to the launcher first, e.g. node beside npx
+        let runtime_binary = resolved.parent()
+            .map(|dir| dir.join(binary))
+            .filter(|p| is_executable(p))
+            .unwrap_or_else(|| PathBuf::from(binary));
+        let key = runtime_binary.to_string_lossy().to_string();
+        check.runtime_version = versions.entry(key)
+            .or_insert_with(|| {
+                run_with_timeout(std::process::Command::new(&runtime_binary).arg("--version"), std::time::Duration::from_secs(5))
+                    .and_then(|v| v.lines().next().map(|l| l.to_string()))
+            })
+            .clone();
+        if check.runtime_version.is_none() {
+            check.note = Some(format!("{} was found but its {} runtime didn't report a version", server.command, runtime));
+        }
+    }
+    check
+}
+
+// ===== Stale Paths =====
+
+// Fragments of the example paths server templates and READMEs ship with
+const PLACEHOLDER_PATTERNS: &[&str] = &[
+    "/path/to/", "\\path\\to\\", "<", ">", "{{", "your-", "your_", "/username/", "\\username\\",
+];
+
+// Pulls a local path out of an argument: a bare path, the value of `--flag=path`, or a
+// `file://` URI. URLs and package names like `@scope/pkg` are not paths.
+fn path_in_value(value: &str) -> Option<&str> {
+    let value = value.split_once('=')
+        .filter(|(flag, _)| flag.starts_with('-'))
+        .map(|(_, rest)| rest)
+        .unwrap_or(value);
+    let value = value.strip_prefix("file://").unwrap_or(value);
+    
+    let is_windows_path = value.len() > 2 && value.as_bytes()[1] == b':' && matches!(value.as_bytes()[2], b'\\' | b'/');
+    let looks_like_path = value.starts_with('/')
+        || value.starts_with("~/")
+        || value.starts_with("./")
+        || value.starts_with("../")
+        || is_windows_path;
+    Some(value).filter(|_| looks_like_path)
+}
+
+fn path_problem(path: &str) -> Option<&'static str> {
+    let lower = path.to_lowercase();
+    if PLACEHOLDER_PATTERNS.iter().any(|p| lower.contains(p)) {
+        return Some("placeholder");
+    }
+    // Relative paths depend on the directory the client starts the server in
+    if path.starts_with("./") || path.starts_with("../") {
+        return Some("relative");
+    }
+    if !expand_home(path).exists() {
+        return Some("missing");
+    }
+    None
+}
+
+fn stale_paths(tool: &str, server: &McpServer) -> Vec<PathFinding> {
+    let server = &unwrap_recording(server);
+    let mut candidates: Vec<(String, &str)> = vec![];
+    if let Some(path) = path_in_value(&server.command) {
+        candidates.push(("command".to_string(), path));
+    }
+    for (idx, arg) in server.args.iter().enumerate() {
+        if let Some(path) = path_in_value(arg) {
+            candidates.push((format!("args[{}]", idx), path));
+        }
+    }
+    let mut env: Vec<(&String, &String)> = server.env.iter().collect();
+    env.sort();
+    for (name, value) in env {
+        if let Some(path) = path_in_value(value) {
+            candidates.push((format!("env.{}", name), path));
+        }
+    }
+    
+    candidates.into_iter()
+        .filter_map(|(field, path)| {
+            path_problem(path).map(|problem| PathFinding {
+                tool: tool.to_string(),
+                server: server.name.clone(),
+                field,
+                path: path.to_string(),
+                problem: problem.to_string(),
+            })
+        })
+        .collect()
+}
+
+// ===== Doctor =====
+
+const ENV_PLACEHOLDER_VALUES: &[&str] = &[
+    "changeme", "change_me", "change-me", "replace_me", "replace-me", "todo",
+];
+
+fn finding(severity: &str, check: &str, tool: &str, server: Option<&str>, message: String, fix: String) -> DoctorFinding {
+    DoctorFinding {
+        severity: severity.to_string(),
+        check: check.to_string(),
+        tool: tool.to_string(),
+        server: server.map(|s| s.to_string()),
+        message,
+        fix,
+    }
+}
+
+fn severity_rank(severity: &str) -> u8 {
+    match severity {
+        "error" => 0,
+        "warning" => 1,
+        _ => 2,
+    }
+}
+
+// Names of the entries in one top-level JSON object, in file order and with duplicates.
+// Parsing into a map would silently keep only the last of two entries with the same name.
+fn json_server_names(content: &str, key: &str) -> Result<Vec<String>, String> {
+    use serde::de::{IgnoredAny, MapAccess, Visitor};
+    
+    struct ObjectKeys(Vec<String>);
+    
+    impl<'de> Deserialize<'de> for ObjectKeys {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<ObjectKeys, D::Error> {
+            deserializer.deserialize_map(KeysVisitor(None)).map(|keys| ObjectKeys(keys.unwrap_or_default()))
+        }
+    }
+    
+    // With a section name, finds that section and returns its keys; without one, returns
+    // the keys of the object itself
+    struct KeysVisitor<'a>(Option<&'a str>);
+    
+    impl<'de> Visitor<'de> for KeysVisitor<'_> {
+        type Value = Option<Vec<String>>;
+        
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a JSON object")
+        }
+        
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut keys = vec![];
+            let mut section = None;
+            while let Some(name) = map.next_key::<String>()? {
+                match self.0 {
+                    Some(key) if name == key => section = Some(map.next_value::<ObjectKeys>()?.0),
+                    _ => {
+                        map.next_value::<IgnoredAny>()?;
+                        keys.push(name);
+                    }
+                }
+            }
+            Ok(if self.0.is_some() { section } else { Some(keys) })
+        }
+    }
+    
+    let mut deserializer = serde_json::Deserializer::from_str(content);
+    serde::Deserializer::deserialize_map(&mut deserializer, KeysVisitor(Some(key)))
+        .map(|names| names.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
+// Server entries straight from the file, as (name, field names). JSON names keep
+// duplicates; TOML rejects duplicate keys while parsing.
+fn raw_server_entries(tool: &ToolInfo, content: &str) -> Result<Vec<(String, Vec<String>)>, String> {
+    if tool.format == "toml" {
+        let config: toml::Value = toml::from_str(content).map_err(|e| e.to_string())?;
+        let servers = match config.get("mcp_servers") {
+            Some(servers) => servers.as_table().ok_or("`mcp_servers` is not a table")?,
+            None => return Ok(vec![]),
+        };
+        return Ok(servers.iter()
+            .map(|(name, server)| {
+                let fields = server.as_table().map(|t| t.keys().cloned().collect()).unwrap_or_default();
+                (name.clone(), fields)
+            })
+            .collect());
+    }
+    
+    let config: serde_json::Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    // Same fallback order as `read_json_servers`
+    let key = match [tool.config_key.as_str(), "mcpServers", "servers"].into_iter().find(|k| config.get(*k).is_some()) {
+        Some(key) => key,
+        None => return Ok(vec![]),
+    };
+    let servers = config[key].as_object().ok_or_else(|| format!("`{}` is not an object", key))?;
+    
+    let names = json_server_names(content, key)?;
+    Ok(names.into_iter()
+        .map(|name| {
+            let fields = servers.get(&name)
+                .and_then(|s| s.as_object())
+                .map(|o| o.keys().cloned().collect())
+                .unwrap_or_default();
+            (name, fields)
+        })
+        .collect())
+}
+
+// Problems with the file itself. Returns the raw entries when it could be parsed.
+fn diagnose_file(tool: &ToolInfo, findings: &mut Vec<DoctorFinding>) -> Option<Vec<(String, Vec<String>)>> {
+    let path = PathBuf::from(&tool.config_path);
+    let name = tool.name.as_str();
+    
+    if !path.exists() {
+        // A built-in tool without a config file is most likely just not installed
+        if tool.is_custom_path || tool.is_custom_tool {
+            findings.push(finding("warning", "missing-file", name, None,
+                format!("{} config file {} doesn't exist", tool.display_name, tool.config_path),
+                "Fix the path in Settings, or add a server to create the file".to_string()));
+        }
+        return None;
+    }
+    
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+            findings.push(finding("error", "permission", name, None,
+                format!("{} can't be read: {}", tool.config_path, e),
+                format!("Give your user read and write access to {}", tool.config_path)));
+            return None;
+        }
+        Err(e) => {
+            findings.push(finding("error", "unparseable", name, None,
+                format!("{} can't be read: {}", tool.config_path, e),
+                "Check that the path points to a text config file".to_string()));
+            return None;
+        }
+    };
+    
+    if fs::metadata(&path).map(|m| m.permissions().readonly()).unwrap_or(false) {
+        findings.push(finding("warning", "permission", name, None,
+            format!("{} is read-only, so changes can't be saved", tool.config_path),
+            format!("Make {} writable", tool.config_path)));
+    }
+    
+    match raw_server_entries(tool, &content) {
+        Ok(entries) => Some(entries),
+        Err(e) => {
+            findings.push(finding("error", "unparseable", name, None,
+                format!("{} can't be parsed: {}", tool.config_path, e),
+                format!("Fix the {} syntax, or restore the file from a backup", tool.format.to_uppercase())));
+            None
+        }
+    }
+}
+
+fn diagnose_entries(tool: &ToolInfo, entries: &[(String, Vec<String>)], findings: &mut Vec<DoctorFinding>) {
+    let name = tool.name.as_str();
+    
+    let mut seen: Vec<&str> = vec![];
+    for (server, _) in entries {
+        let count = entries.iter().filter(|(other, _)| other == server).count();
+        if count > 1 && !seen.contains(&server.as_str()) {
+            findings.push(finding("error", "duplicate-name", name, Some(server),
+                format!("{} is defined {} times; only the last definition is used", server, count),
+                "Remove or rename the extra definitions".to_string()));
+        } else if count == 1 && seen.iter().any(|s| s.eq_ignore_ascii_case(server)) {
+            findings.push(finding("warning", "duplicate-name", name, Some(server),
+                format!("{} differs from another server only in letter case", server),
+                "Rename one of them so they're easy to tell apart".to_string()));
+        }
+        seen.push(server);
+    }
+    
+    // Custom tools have no documented field list to check against
+    let known = match get_tool_definitions().into_iter().find(|def| def.name == name) {
+        Some(def) => def.server_fields,
+        None => return,
+    };
+    for (server, fields) in entries {
+        for field in fields.iter().filter(|f| !known.contains(&f.as_str())) {
+            // MCP Manager writes `disabled` itself, so this one needs a different fix
+            let (message, fix) = if field == "disabled" {
+                (format!("{} doesn't document `disabled`, so it may still start this server", tool.display_name),
+                    format!("Delete the server from {} if it must not run", tool.display_name))
+            } else {
+                (format!("`{}` isn't a server field {} documents, so it's probably ignored", field, tool.display_name),
+                    format!("Remove `{}`, or check {}'s documentation for the equivalent setting", field, tool.display_name))
+            };
+            findings.push(finding("warning", "unsupported-field", name, Some(server), message, fix));
+        }
+    }
+}
+
+// Only values that are nothing but a placeholder count: real tokens and passwords can
+// contain `<`, `xxxx` or `todo` anywhere
+fn is_env_placeholder(value: &str) -> bool {
+    let lower = value.trim().to_lowercase();
+    let is_word = |v: &str| !v.is_empty() && v.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    (lower.len() > 2 && lower.starts_with('<') && lower.ends_with('>'))
+        || (lower.matches('x').count() >= 4 && lower.chars().all(|c| matches!(c, 'x' | '-' | '_')))
+        || ["your_", "your-"].iter().any(|p| lower.strip_prefix(p).is_some_and(is_word))
+        || (lower.starts_with("your ") && lower.ends_with(" here"))
+        || ENV_PLACEHOLDER_VALUES.contains(&lower.as_str())
+}
+
+fn env_problem(value: &str) -> Option<(&'static str, String)> {
+    if value.trim().is_empty() {
+        return Some(("warning", "is empty".to_string()));
+    }
+    if is_env_placeholder(value) {
+        return Some(("error", format!("still holds the placeholder {}", value)));
+    }
+    
+    // `${VAR}`, `${env:VAR}` and `$VAR` are filled in from the environment by some clients.
+    // A bare `$` is only taken for a reference when an upper-case variable name follows,
+    // since passwords start with `$` too.
+    let is_var_name = |v: &str| v.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && v.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    let reference = value.strip_prefix("${").and_then(|v| v.strip_suffix('}'))
+        .map(|v| v.strip_prefix("env:").unwrap_or(v))
+        .filter(|v| is_var_name(v))
+        .or_else(|| value.strip_prefix('$').filter(|v| is_var_name(v) && !v.chars().any(|c| c.is_ascii_lowercase())));
+    match reference {
+        Some(var) if std::env::var(var).is_err() => {
+            Some(("warning", format!("refers to ${} which isn't set in MCP Manager's environment", var)))
+        }
+        _ => None,
+    }
+}
+
+fn diagnose_servers(tool: &ToolInfo, shell_path: Option<&str>, versions: &mut HashMap<String, Option<String>>, findings: &mut Vec<DoctorFinding>) {
+    let name = tool.name.as_str();
+    
+    // Disabled servers don't start, so their problems can't be why something isn't working
+    for server in read_servers(name).iter().filter(|s| s.enabled) {
+        let server_name = Some(server.name.as_str());
+        
+        if server.server_type == "stdio" && !server.command.is_empty() {
+            let check = check_command(name, server, shell_path, versions);
+            match check.status.as_str() {
+                "missing" => findings.push(finding("error", "missing-command", name, server_name,
+                    format!("{} isn't installed or isn't on the PATH", server.command),
+                    command_fix(&server.command))),
+                "not-executable" => findings.push(finding("error", "missing-command", name, server_name,
+                    format!("{} isn't executable", check.resolved_path.unwrap_or_default()),
+                    "Make the file executable (chmod +x)".to_string())),
+                _ => {
+                    if let Some(note) = check.note {
+                        findings.push(finding("warning", "missing-command", name, server_name, note,
+                            format!("Use the full path to {} as the command", server.command)));
+                    }
+                }
+            }
+        }
+        
+        let mut env: Vec<(&String, &String)> = server.env.iter().collect();
+        env.sort();
+        for (var, value) in env {
+            if let Some((severity, problem)) = env_problem(value) {
+                findings.push(finding(severity, "unset-env", name, server_name,
+                    format!("{} {}", var, problem),
+                    format!("Set {} to a real value in the server's env", var)));
+            }
+        }
+        
+        for path in stale_paths(name, server) {
+            let (severity, message, fix) = match path.problem.as_str() {
+                "placeholder" => ("error", format!("{} is a template placeholder: {}", path.field, path.path),
+                    "Replace it with a real path".to_string()),
+                "relative" => ("info", format!("{} is relative: {}", path.field, path.path),
+                    "Use an absolute path; clients start servers from different directories".to_string()),
+                _ => ("warning", format!("{} doesn't exist: {}", path.field, path.path),
+                    "Update the path, or recreate the file or directory".to_string()),
+            };
+            findings.push(finding(severity, "stale-path", name, server_name, message, fix));
+        }
+    }
+}
+
+fn command_fix(command: &str) -> String {
+    match command_runtime(command).map(|(runtime, _)| runtime) {
+        Some("node") => "Install Node.js, which provides node, npm and npx".to_string(),
+        Some("uv") => "Install uv, which provides uvx".to_string(),
+        Some("python") => "Install Python 3".to_string(),
+        Some("docker") => "Install Docker and make sure it is running".to_string(),
+        Some(runtime) => format!("Install {}", runtime),
+        None => format!("Install {} or use the full path to it as the command", command),
+    }
+}
+
+fn run_doctor(tools: &[ToolInfo]) -> Vec<DoctorFinding> {
+    let shell_path = login_shell_path();
+    let mut versions = HashMap::new();
+    let mut findings = vec![];
+    
+    for tool in tools {
+        if let Some(entries) = diagnose_file(tool, &mut findings) {
+            diagnose_entries(tool, &entries, &mut findings);
+            diagnose_servers(tool, shell_path.as_deref(), &mut versions, &mut findings);
+        }
+    }
+    
+    // Stable, so findings of equal severity stay grouped by tool and server
+    findings.sort_by_key(|f| severity_rank(&f.severity));
+    findings
+}
+
+// ===== Tauri Commands Module =====
+// Public so the CLI binary runs the same code paths as the app. Commands are only
+// registered with Tauri in the `gui` build; the inspector's need its state.
+pub mod commands {
+    use super::*;
+
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn get_tools() -> Vec<ToolInfo> {
+        let home = get_home_dir();
+        let settings = load_settings();
+        
+        let mut tools: Vec<ToolInfo> = get_tool_definitions().iter().map(|def| {
+            let default_path = (def.path_fn)(&home);
+            let custom_path = settings.custom_paths.get(def.name);
+            let is_custom = custom_path.map(|p| !p.is_empty()).unwrap_or(false);
+            
+            let actual_path = if is_custom {
+                expand_home(custom_path.unwrap())
+            } else {
+                default_path
+            };
+            
+            ToolInfo {
+                name: def.name.to_string(),
+                display_name: def.display_name.to_string(),
                 config_path: actual_path.to_string_lossy().to_string(),
                 config_key: def.config_key.to_string(),
                 exists: actual_path.exists(),
@@ -497,11 +3964,7 @@ mod commands {
         
         // Add custom tools from settings
         for custom_tool in &settings.custom_tools {
-            let path = if custom_tool.config_path.starts_with("~") {
-                home.join(custom_tool.config_path.trim_start_matches("~/"))
-            } else {
-                PathBuf::from(&custom_tool.config_path)
-            };
+            let path = expand_home(&custom_tool.config_path);
             
             tools.push(ToolInfo {
                 name: custom_tool.name.clone(),
@@ -518,23 +3981,68 @@ mod commands {
         tools
     }
 
-    #[tauri::command]
+    #[cfg_attr(feature = "gui", tauri::command)]
     pub fn get_settings() -> AppSettings {
         load_settings()
     }
 
-    #[tauri::command]
+    #[cfg_attr(feature = "gui", tauri::command)]
     pub fn update_tool_path(tool: String, path: String) -> Result<(), String> {
         let mut settings = load_settings();
         if path.is_empty() {
             settings.custom_paths.remove(&tool);
         } else {
+            validate_config_path(&path)?;
             settings.custom_paths.insert(tool, path);
         }
         save_settings(&settings)
     }
 
-    #[tauri::command]
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn update_backup_retention(policy: RetentionPolicy) -> Result<(), String> {
+        let mut settings = load_settings();
+        settings.backup_retention = policy;
+        save_settings(&settings)?;
+        prune_snapshots(&settings.backup_retention)?;
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn update_backup_schedule(schedule: BackupSchedule) -> Result<(), String> {
+        if schedule.interval_minutes < 1 {
+            return Err("Backup interval must be at least one minute".to_string());
+        }
+        let mut settings = load_settings();
+        settings.scheduled_backup = schedule;
+        save_settings(&settings)
+    }
+
+    // Replaces the extra backup destinations. Existing backups are not copied over;
+    // new manual and scheduled backups are written to every destination from now on.
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn update_backup_destinations(destinations: Vec<String>) -> Result<Vec<String>, String> {
+        let mut cleaned = vec![];
+        for destination in destinations.iter().map(|d| d.trim()).filter(|d| !d.is_empty()) {
+            validate_backup_destination(destination)?;
+            if !cleaned.iter().any(|d: &String| d == destination) {
+                cleaned.push(destination.to_string());
+            }
+        }
+        
+        let mut settings = load_settings();
+        settings.backup_destinations = cleaned.clone();
+        save_settings(&settings)?;
+        Ok(cleaned)
+    }
+
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn update_snapshot_destinations(enabled: bool) -> Result<(), String> {
+        let mut settings = load_settings();
+        settings.snapshot_destinations = enabled;
+        save_settings(&settings)
+    }
+
+    #[cfg_attr(feature = "gui", tauri::command)]
     pub fn add_custom_tool(tool: CustomTool) -> Result<Vec<CustomTool>, String> {
         let mut settings = load_settings();
         
@@ -548,14 +4056,17 @@ mod commands {
             return Err(format!("Tool '{}' already exists", tool.name));
         }
         
+        validate_config_path(&tool.config_path)?;
+        
         settings.custom_tools.push(tool);
         save_settings(&settings)?;
         Ok(settings.custom_tools)
     }
 
-    #[tauri::command]
+    #[cfg_attr(feature = "gui", tauri::command)]
     pub fn update_custom_tool(name: String, tool: CustomTool) -> Result<Vec<CustomTool>, String> {
         let mut settings = load_settings();
+        validate_config_path(&tool.config_path)?;
         
         if let Some(idx) = settings.custom_tools.iter().position(|t| t.name == name) {
             settings.custom_tools[idx] = tool;
@@ -566,7 +4077,7 @@ mod commands {
         }
     }
 
-    #[tauri::command]
+    #[cfg_attr(feature = "gui", tauri::command)]
     pub fn delete_custom_tool(name: String) -> Result<Vec<CustomTool>, String> {
         let mut settings = load_settings();
         let before_len = settings.custom_tools.len();
@@ -580,19 +4091,20 @@ mod commands {
         Ok(settings.custom_tools)
     }
 
-    #[tauri::command]
+    #[cfg_attr(feature = "gui", tauri::command)]
     pub fn get_all_configs() -> HashMap<String, Vec<McpServer>> {
         get_all_configs_internal()
     }
 
-    #[tauri::command]
+    #[cfg_attr(feature = "gui", tauri::command)]
     pub fn get_configs(tool: String) -> Vec<McpServer> {
         read_servers(&tool)
     }
 
-    #[tauri::command]
+    #[cfg_attr(feature = "gui", tauri::command)]
     pub fn add_or_update_server(tool: String, server: McpServer) -> Result<Vec<McpServer>, String> {
         let mut servers = read_servers(&tool);
+        let server_name = server.name.clone();
         
         if let Some(idx) = servers.iter().position(|s| s.name == server.name) {
             servers[idx] = server;
@@ -600,19 +4112,23 @@ mod commands {
             servers.push(server);
         }
         
+        let pending = begin_change(&[&tool], "pre-write")?;
         write_servers(&tool, &servers)?;
+        finish_change("add_or_update_server", pending, &[(tool, vec![server_name])]);
         Ok(servers)
     }
 
-    #[tauri::command]
+    #[cfg_attr(feature = "gui", tauri::command)]
     pub fn delete_server(tool: String, server_name: String) -> Result<Vec<McpServer>, String> {
         let mut servers = read_servers(&tool);
         servers.retain(|s| s.name != server_name);
+        let pending = begin_change(&[&tool], "pre-write")?;
         write_servers(&tool, &servers)?;
+        finish_change("delete_server", pending, &[(tool, vec![server_name])]);
         Ok(servers)
     }
 
-    #[tauri::command]
+    #[cfg_attr(feature = "gui", tauri::command)]
     pub fn toggle_server(tool: String, server_name: String) -> Result<McpServer, String> {
         let mut servers = read_servers(&tool);
         
@@ -623,21 +4139,69 @@ mod commands {
         server.enabled = !server.enabled;
         let result = server.clone();
         
+        let pending = begin_change(&[&tool], "pre-write")?;
+        write_servers(&tool, &servers)?;
+        finish_change("toggle_server", pending, &[(tool, vec![server_name])]);
+        Ok(result)
+    }
+
+    // Wraps the server's command with the recording shim, or restores the original command
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn set_transcript_recording(tool: String, server_name: String, enabled: bool) -> Result<McpServer, String> {
+        let mut servers = read_servers(&tool);
+        
+        let server = servers.iter_mut()
+            .find(|s| s.name == server_name)
+            .ok_or_else(|| format!("Server not found: {}", server_name))?;
+        if server.server_type != "stdio" {
+            return Err("Only stdio servers can be recorded".to_string());
+        }
+        
+        match (enabled, recorded_command(server)) {
+            (true, None) => wrap_for_recording(&tool, server)?,
+            (false, Some(_)) => *server = unwrap_recording(server),
+            _ => return Ok(server.clone()),
+        }
+        let result = server.clone();
+        
+        let pending = begin_change(&[&tool], "pre-write")?;
         write_servers(&tool, &servers)?;
+        finish_change("set_transcript_recording", pending, &[(tool, vec![server_name])]);
         Ok(result)
     }
 
-    #[tauri::command]
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn get_transcripts(tool: String, server_name: String) -> Vec<TranscriptInfo> {
+        list_transcripts(&tool, &server_name)
+    }
+
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn get_transcript(tool: String, server_name: String, name: String) -> Result<Vec<TranscriptEntry>, String> {
+        read_transcript(&transcript_path(&tool, &server_name, &name)?)
+    }
+
+    #[cfg_attr(feature = "gui", tauri::command(async))]
+    pub fn replay_transcript(tool: String, server_name: String, name: String) -> Result<TranscriptReplay, String> {
+        let entries = read_transcript(&transcript_path(&tool, &server_name, &name)?)?;
+        let server = read_servers(&tool).into_iter()
+            .find(|s| s.name == server_name)
+            .ok_or_else(|| format!("Server not found: {}", server_name))?;
+        super::replay_transcript(&server, &entries)
+    }
+
+    #[cfg_attr(feature = "gui", tauri::command)]
     pub fn sync_configs(from: String, to: String, server_names: Option<Vec<String>>) -> Result<usize, String> {
         let from_servers = read_servers(&from);
         let mut to_servers = read_servers(&to);
         
+        // The recording wrapper names this app's binary and the source tool, so it stays behind
         let servers_to_sync: Vec<_> = match server_names {
             Some(names) => from_servers.into_iter().filter(|s| names.contains(&s.name)).collect(),
             None => from_servers,
-        };
+        }.iter().map(unwrap_recording).collect();
         
         let count = servers_to_sync.len();
+        let synced: Vec<String> = servers_to_sync.iter().map(|s| s.name.clone()).collect();
         
         for server in servers_to_sync {
             if let Some(idx) = to_servers.iter().position(|s| s.name == server.name) {
@@ -647,98 +4211,461 @@ mod commands {
             }
         }
         
+        let pending = begin_change(&[&to], "pre-sync")?;
         write_servers(&to, &to_servers)?;
+        finish_change("sync_configs", pending, &[(to, synced)]);
         Ok(count)
     }
 
-    #[tauri::command]
-    pub fn get_backups() -> Vec<Backup> {
-        let backup_dir = get_backup_dir();
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn get_journal() -> Vec<JournalSummary> {
+        let journal = load_journal();
+        journal.entries.iter().enumerate()
+            .map(|(idx, entry)| summarize_journal_entry(entry, idx >= journal.cursor))
+            .collect()
+    }
+
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn undo(force: Option<bool>) -> Result<JournalSummary, String> {
+        let mut journal = load_journal();
+        if journal.cursor == 0 {
+            return Err("Nothing to undo".to_string());
+        }
         
-        if !backup_dir.exists() {
-            return vec![];
+        let entry = journal.entries[journal.cursor - 1].clone();
+        apply_journal_entry(&entry, true, force.unwrap_or(false))?;
+        journal.cursor -= 1;
+        save_journal(&journal)?;
+        Ok(summarize_journal_entry(&entry, true))
+    }
+
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn redo(force: Option<bool>) -> Result<JournalSummary, String> {
+        let mut journal = load_journal();
+        if journal.cursor >= journal.entries.len() {
+            return Err("Nothing to redo".to_string());
         }
         
-        fs::read_dir(&backup_dir)
-            .ok()
-            .map(|entries| {
-                entries
-                    .filter_map(|e| e.ok())
-                    .filter(|e| e.path().extension().map(|ext| ext == "json").unwrap_or(false))
-                    .map(|e| {
-                        let name = e.file_name().to_string_lossy().to_string();
-                        let timestamp = name.replace("backup-", "").replace(".json", "");
-                        Backup { name, timestamp }
-                    })
-                    .collect()
-            })
-            .unwrap_or_default()
+        let entry = journal.entries[journal.cursor].clone();
+        apply_journal_entry(&entry, false, force.unwrap_or(false))?;
+        journal.cursor += 1;
+        save_journal(&journal)?;
+        Ok(summarize_journal_entry(&entry, false))
+    }
+
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn get_history(limit: Option<usize>) -> Result<Vec<HistoryEntry>, String> {
+        history_log(limit, None)
+    }
+
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn get_server_history(tool: String, server_name: String, limit: Option<usize>) -> Result<Vec<HistoryEntry>, String> {
+        let path = format!("{}/{}.json", encode_history_name(&tool), encode_history_name(&server_name));
+        history_log(limit, Some(&path))
     }
 
-    #[tauri::command]
-    pub fn create_backup() -> Result<Backup, String> {
-        let backup_dir = get_backup_dir();
-        fs::create_dir_all(&backup_dir).map_err(|e| e.to_string())?;
+    // Writes the servers recorded at `commit` back into the tools' config files
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn checkout_history(commit: String, tools: Option<Vec<String>>) -> Result<Vec<String>, String> {
+        // Only plain commit hashes, so the argument can never be read as a git option
+        if commit.len() < 4 || !commit.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("Invalid commit: {}", commit));
+        }
+        if !get_history_dir().join(".git").exists() {
+            return Err("No configuration history recorded yet".to_string());
+        }
         
-        let timestamp = chrono::Local::now().format("%Y-%m-%dT%H-%M-%S").to_string();
-        let filename = format!("backup-{}.json", timestamp);
-        let path = backup_dir.join(&filename);
+        let recorded = read_history_servers(&commit)?;
+        let to_restore: Vec<(String, Vec<McpServer>)> = recorded.into_iter()
+            .filter(|(tool, _)| tools.as_ref().map(|t| t.contains(tool)).unwrap_or(true))
+            // Every tool has a directory in the history, installed or not: only write
+            // the ones that differ, and never create a config just to hold no servers
+            .filter(|(tool, servers)| !same_servers(servers, &read_servers(tool)))
+            .filter(|(tool, servers)| !servers.is_empty() || config_exists(tool))
+            .collect();
+        
+        let affected: Vec<String> = to_restore.iter().map(|(tool, _)| tool.clone()).collect();
+        let pending = begin_change(&affected, "pre-restore")?;
+        
+        let mut changes = vec![];
+        for (tool, servers) in to_restore {
+            write_servers(&tool, &servers)?;
+            changes.push((tool, servers.iter().map(|s| s.name.clone()).collect()));
+        }
+        
+        finish_change(&format!("checkout_history {}", &commit[..commit.len().min(12)]), pending, &changes);
+        Ok(affected)
+    }
+
+    // Starts or connects to the server and runs the MCP handshake, so it runs off the main thread
+    #[cfg_attr(feature = "gui", tauri::command(async))]
+    pub fn check_server(tool: String, server: McpServer, timeout_ms: Option<u64>) -> HealthCheck {
+        let timeout = std::time::Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_CHECK_TIMEOUT_MS));
+        check_server_health(&tool, &server, timeout)
+    }
+
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn get_health_history(tool: String, server_name: String, limit: Option<usize>) -> HealthTimeline {
+        health_timeline(&tool, &server_name, limit.unwrap_or(100))
+    }
+
+    // Inventory of one server, or of every enabled server in the tool when no name is given
+    #[cfg_attr(feature = "gui", tauri::command(async))]
+    pub fn get_inventory(tool: String, server_name: Option<String>, refresh: Option<bool>) -> Result<Vec<ServerInventory>, String> {
+        let servers: Vec<McpServer> = read_servers(&tool).into_iter()
+            .filter(|s| match server_name {
+                Some(ref name) => &s.name == name,
+                None => s.enabled,
+            })
+            .collect();
+        
+        if let Some(name) = server_name {
+            if servers.is_empty() {
+                return Err(format!("Server {} not found in {}", name, tool));
+            }
+        }
+        Ok(server_inventories(&servers, refresh.unwrap_or(false)))
+    }
+
+    // Budget for one tool, or for every tool with enabled servers when none is given
+    #[cfg_attr(feature = "gui", tauri::command(async))]
+    pub fn estimate_token_budget(tool: Option<String>, refresh: Option<bool>) -> Vec<TokenBudget> {
+        let configs: HashMap<String, Vec<McpServer>> = match tool {
+            Some(tool) => HashMap::from([(tool.clone(), read_servers(&tool))]),
+            None => get_all_configs_internal().into_iter()
+                .filter(|(_, servers)| servers.iter().any(|s| s.enabled))
+                .collect(),
+        };
+        let inventories = inventories_for(&configs, refresh.unwrap_or(false));
         
-        let backup_data = BackupData {
-            timestamp: timestamp.clone(),
-            tools: get_all_configs_internal(),
+        let mut budgets: Vec<TokenBudget> = configs.iter()
+            .map(|(tool, servers)| token_budget(tool, servers, &inventories))
+            .collect();
+        budgets.sort_by_key(|b| std::cmp::Reverse(b.total_tokens));
+        budgets
+    }
+
+    #[cfg_attr(feature = "gui", tauri::command(async))]
+    pub fn find_tool_collisions(tool: Option<String>, refresh: Option<bool>) -> Vec<ToolCollision> {
+        let mut configs: HashMap<String, Vec<McpServer>> = match tool {
+            Some(tool) => HashMap::from([(tool.clone(), read_servers(&tool))]),
+            None => get_all_configs_internal(),
         };
+        // Skipped before probing, so their servers aren't started for nothing
+        configs.retain(|tool, _| !NAMESPACED_TOOL_CLIENTS.contains(&tool.as_str()));
+        let inventories = inventories_for(&configs, refresh.unwrap_or(false));
+        
+        let mut collisions: Vec<ToolCollision> = configs.iter()
+            .flat_map(|(tool, servers)| tool_collisions(tool, servers, &inventories))
+            .collect();
+        collisions.sort_by(|a, b| a.tool.cmp(&b.tool).then_with(|| a.tool_name.cmp(&b.tool_name)));
+        collisions
+    }
+
+    // Resolves the command of every stdio server in every tool
+    #[cfg_attr(feature = "gui", tauri::command(async))]
+    pub fn check_commands() -> Vec<CommandCheck> {
+        let shell_path = login_shell_path();
+        let mut versions = HashMap::new();
+        let mut configs: Vec<(String, Vec<McpServer>)> = get_all_configs_internal().into_iter().collect();
+        configs.sort_by(|a, b| a.0.cmp(&b.0));
+        
+        let mut checks = vec![];
+        for (tool, servers) in configs {
+            for server in servers.iter().filter(|s| s.server_type == "stdio" && !s.command.is_empty()) {
+                checks.push(check_command(&tool, server, shell_path.as_deref(), &mut versions));
+            }
+        }
+        checks
+    }
+
+    // Paths in every server's command, args and env that don't exist or were never filled in
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn check_paths() -> Vec<PathFinding> {
+        let mut configs: Vec<(String, Vec<McpServer>)> = get_all_configs_internal().into_iter().collect();
+        configs.sort_by(|a, b| a.0.cmp(&b.0));
+        
+        configs.iter()
+            .flat_map(|(tool, servers)| servers.iter().flat_map(move |server| stale_paths(tool, server)))
+            .collect()
+    }
+
+    // Every configuration problem across all tools, most severe first
+    #[cfg_attr(feature = "gui", tauri::command(async))]
+    pub fn doctor() -> Vec<DoctorFinding> {
+        run_doctor(&get_tools())
+    }
+
+    #[cfg(feature = "gui")]
+    #[tauri::command(async)]
+    pub fn open_inspector(
+        sessions: tauri::State<'_, InspectorSessions>,
+        tool: String,
+        server_name: String,
+    ) -> Result<InspectorSession, String> {
+        let server = read_servers(&tool).into_iter()
+            .find(|s| s.name == server_name)
+            .ok_or_else(|| format!("Server {} not found in {}", server_name, tool))?;
+        sessions.open(&server)
+    }
+
+    #[cfg(feature = "gui")]
+    #[tauri::command(async)]
+    pub fn inspector_call_tool(
+        sessions: tauri::State<'_, InspectorSessions>,
+        session_id: String,
+        name: String,
+        arguments: Option<serde_json::Value>,
+        timeout_ms: Option<u64>,
+    ) -> Result<InspectorResponse, String> {
+        let params = serde_json::json!({ "name": name, "arguments": arguments.unwrap_or_else(|| serde_json::json!({})) });
+        sessions.request(&session_id, "tools/call", params, timeout_ms)
+    }
+
+    #[cfg(feature = "gui")]
+    #[tauri::command(async)]
+    pub fn inspector_read_resource(
+        sessions: tauri::State<'_, InspectorSessions>,
+        session_id: String,
+        uri: String,
+        timeout_ms: Option<u64>,
+    ) -> Result<InspectorResponse, String> {
+        sessions.request(&session_id, "resources/read", serde_json::json!({ "uri": uri }), timeout_ms)
+    }
+
+    #[cfg(feature = "gui")]
+    #[tauri::command(async)]
+    pub fn inspector_get_prompt(
+        sessions: tauri::State<'_, InspectorSessions>,
+        session_id: String,
+        name: String,
+        arguments: Option<HashMap<String, String>>,
+        timeout_ms: Option<u64>,
+    ) -> Result<InspectorResponse, String> {
+        let params = serde_json::json!({ "name": name, "arguments": arguments.unwrap_or_default() });
+        sessions.request(&session_id, "prompts/get", params, timeout_ms)
+    }
+
+    #[cfg(feature = "gui")]
+    #[tauri::command(async)]
+    pub fn close_inspector(sessions: tauri::State<'_, InspectorSessions>, session_id: String) -> Result<String, String> {
+        sessions.close(&session_id)
+    }
+
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn get_backups() -> Vec<Backup> {
+        let mut backups = list_backups();
+        backups.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        backups
+    }
+
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn create_backup(label: Option<String>, note: Option<String>, passphrase: Option<String>) -> Result<Backup, String> {
+        let timestamp = backup_timestamp();
+        let filename = format!("backup-{}.json", timestamp);
+        let backup_data = gather_backup_data(timestamp, "manual", label, note);
         
-        let content = serde_json::to_string_pretty(&backup_data).map_err(|e| e.to_string())?;
-        fs::write(&path, content).map_err(|e| e.to_string())?;
+        store_backup(&filename, &backup_data, passphrase.as_deref())?;
         
-        Ok(Backup { name: filename, timestamp })
+        list_backups().into_iter()
+            .find(|b| b.name == filename)
+            .ok_or_else(|| format!("Backup not found: {}", filename))
     }
 
-    #[tauri::command]
-    pub fn restore_backup(filename: String, tools_to_restore: Option<Vec<String>>) -> Result<Vec<String>, String> {
-        let backup_dir = get_backup_dir();
-        let path = backup_dir.join(&filename);
+    // `mode` is "servers" (default) to rewrite only the server entries, or "raw" to put
+    // the backed-up config files back exactly as they were
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn restore_backup(
+        filename: String,
+        tools_to_restore: Option<Vec<String>>,
+        mode: Option<String>,
+        passphrase: Option<String>,
+    ) -> Result<Vec<String>, String> {
+        let backup = load_backup(&BackupId::parse(&filename)?, passphrase.as_deref())?;
+        
+        let raw = match mode.as_deref() {
+            None | Some("servers") => false,
+            Some("raw") => true,
+            Some(other) => return Err(format!("Unknown restore mode: {}", other)),
+        };
+        
+        if raw && backup.files.is_empty() {
+            return Err(format!("Backup {} has no raw file copies", filename));
+        }
+        
+        let to_restore: Vec<(String, Vec<McpServer>)> = backup.tools.into_iter()
+            .filter(|(tool, _)| tools_to_restore.as_ref().map(|t| t.contains(tool)).unwrap_or(true))
+            // Tools whose file didn't exist at backup time have no raw copy to put back
+            .filter(|(tool, _)| !raw || backup.files.contains_key(tool))
+            .collect();
         
-        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
-        let backup: BackupData = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+        let affected: Vec<String> = to_restore.iter().map(|(tool, _)| tool.clone()).collect();
+        let pending = begin_change(&affected, "pre-restore")?;
+        
+        // Settings go first so custom tools and paths resolve when the configs are written
+        if let Some(ref settings) = backup.settings {
+            restore_tool_settings(settings, &affected)?;
+        }
         
         let mut restored = vec![];
+        let mut changes = vec![];
         
-        for (tool, servers) in backup.tools {
-            if let Some(ref tools) = tools_to_restore {
-                if !tools.contains(&tool) {
-                    continue;
-                }
+        for (tool, servers) in to_restore {
+            if raw {
+                write_raw_config(&tool, &backup.files[&tool].content)?;
+            } else {
+                write_servers(&tool, &servers)?;
             }
-            
-            write_servers(&tool, &servers)?;
+            changes.push((tool.clone(), servers.iter().map(|s| s.name.clone()).collect()));
             restored.push(tool);
         }
         
+        finish_change("restore_backup", pending, &changes);
         Ok(restored)
     }
 
-    #[tauri::command]
+    // Brings back individual servers from a backup, merged into the current configs
+    // so other servers in the same tool are left untouched
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn restore_servers(
+        filename: String,
+        servers: Vec<ServerSelection>,
+        passphrase: Option<String>,
+    ) -> Result<Vec<ServerSelection>, String> {
+        let backup = load_backup(&BackupId::parse(&filename)?, passphrase.as_deref())?;
+        
+        let mut by_tool: HashMap<String, Vec<McpServer>> = HashMap::new();
+        for selection in &servers {
+            let server = backup.tools.get(&selection.tool)
+                .and_then(|list| list.iter().find(|s| s.name == selection.server))
+                .ok_or_else(|| format!("Server {} not found for {} in backup {}", selection.server, selection.tool, filename))?;
+            by_tool.entry(selection.tool.clone()).or_default().push(server.clone());
+        }
+        
+        let affected: Vec<String> = by_tool.keys().cloned().collect();
+        let pending = begin_change(&affected, "pre-restore")?;
+        
+        if let Some(ref settings) = backup.settings {
+            restore_tool_settings(settings, &affected)?;
+        }
+        
+        let mut changes = vec![];
+        
+        for (tool, restored) in by_tool {
+            let names: Vec<String> = restored.iter().map(|s| s.name.clone()).collect();
+            let mut current = read_servers(&tool);
+            for server in restored {
+                if let Some(idx) = current.iter().position(|s| s.name == server.name) {
+                    current[idx] = server;
+                } else {
+                    current.push(server);
+                }
+            }
+            write_servers(&tool, &current)?;
+            changes.push((tool, names));
+        }
+        
+        finish_change("restore_servers", pending, &changes);
+        Ok(servers)
+    }
+
+    // Shows what restoring the backup would change: `before` is the live config and
+    // `after` is the backup. Only tools contained in the backup are compared.
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn diff_backup(filename: String, passphrase: Option<String>) -> Result<Vec<ToolDiff>, String> {
+        let backup = load_backup(&BackupId::parse(&filename)?, passphrase.as_deref())?;
+        let tools: Vec<String> = backup.tools.keys().cloned().collect();
+        let current: HashMap<String, Vec<McpServer>> = tools.iter()
+            .map(|tool| (tool.clone(), read_servers(tool)))
+            .collect();
+        
+        Ok(diff_configs(&current, &backup.tools, &tools))
+    }
+
+    // The passphrase is used for whichever of the two backups is encrypted
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn diff_backups(from: String, to: String, passphrase: Option<String>) -> Result<Vec<ToolDiff>, String> {
+        let from_backup = load_backup(&BackupId::parse(&from)?, passphrase.as_deref())?;
+        let to_backup = load_backup(&BackupId::parse(&to)?, passphrase.as_deref())?;
+        let tools: Vec<String> = from_backup.tools.keys().chain(to_backup.tools.keys()).cloned().collect();
+        
+        Ok(diff_configs(&from_backup.tools, &to_backup.tools, &tools))
+    }
+
+    #[cfg_attr(feature = "gui", tauri::command)]
     pub fn delete_backup(filename: String) -> Result<(), String> {
-        let backup_dir = get_backup_dir();
-        let path = backup_dir.join(&filename);
-        fs::remove_file(&path).map_err(|e| e.to_string())
+        let id = BackupId::parse(&filename)?;
+        let _lock = lock_backups();
+        for backup_dir in id.origins() {
+            fs::remove_file(backup_dir.join(id.name())).map_err(|e| e.to_string())?;
+            collect_garbage(backup_dir)?;
+        }
+        Ok(())
     }
 
-    #[tauri::command]
-    pub fn export_configs() -> BackupData {
-        BackupData {
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn verify_backups() -> Vec<BackupCheck> {
+        let mut checks: Vec<BackupCheck> = get_backup_dirs().iter()
+            .flat_map(|dir| list_backup_files(dir).into_iter().map(move |path| verify_backup(dir, &path)))
+            .collect();
+        checks.sort_by(|a, b| b.name.cmp(&a.name).then_with(|| a.origin.cmp(&b.origin)));
+        checks
+    }
+
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn export_configs(passphrase: Option<String>) -> Result<ExportBundle, String> {
+        // Raw files stay out of exports: they can hold unrelated settings from the tool.
+        // Neither does the recording wrapper, which only works on this machine.
+        let tools: HashMap<String, Vec<McpServer>> = get_all_configs_internal().into_iter()
+            .filter(|(tool, servers)| !servers.is_empty() || config_exists(tool))
+            .map(|(tool, servers)| (tool, servers.iter().map(unwrap_recording).collect()))
+            .collect();
+        let data = BackupData {
+            version: BACKUP_FORMAT_VERSION,
             timestamp: chrono::Local::now().format("%Y-%m-%dT%H-%M-%S").to_string(),
-            tools: get_all_configs_internal(),
+            metadata: backup_metadata(&tools, "manual", None, None),
+            tools,
+            files: HashMap::new(),
+            settings: None,
+        };
+        
+        match passphrase {
+            Some(passphrase) => Ok(ExportBundle::Encrypted(encrypt_backup(&data, &passphrase)?)),
+            None => Ok(ExportBundle::Plain(data)),
         }
     }
 
-    #[tauri::command]
-    pub fn import_configs(tools: HashMap<String, Vec<McpServer>>, merge: bool) -> Result<Vec<String>, String> {
+    // Takes either the plain `tools` map of an export, or an encrypted export `bundle`
+    // together with its passphrase
+    #[cfg_attr(feature = "gui", tauri::command)]
+    pub fn import_configs(
+        tools: Option<HashMap<String, Vec<McpServer>>>,
+        bundle: Option<EncryptedBundle>,
+        passphrase: Option<String>,
+        merge: bool,
+    ) -> Result<Vec<String>, String> {
+        let tools = match (tools, bundle) {
+            (_, Some(bundle)) => {
+                let passphrase = passphrase.ok_or("This export is encrypted, a passphrase is required")?;
+                decrypt_backup(&bundle, &passphrase)?.tools
+            }
+            (Some(tools), None) => tools,
+            (None, None) => return Err("Nothing to import".to_string()),
+        };
+        // An empty list for a tool that isn't set up here would only create an empty config
+        let tools: HashMap<String, Vec<McpServer>> = tools.into_iter()
+            .filter(|(tool, servers)| !servers.is_empty() || config_exists(tool))
+            .collect();
+        
+        let affected: Vec<String> = tools.keys().cloned().collect();
+        let pending = begin_change(&affected, "pre-import")?;
+        
         let mut imported = vec![];
+        let mut changes = vec![];
         
         for (tool, servers) in tools {
+            let servers: Vec<McpServer> = servers.iter().map(unwrap_recording).collect();
+            changes.push((tool.clone(), servers.iter().map(|s| s.name.clone()).collect()));
             let final_servers = if merge {
                 let mut existing = read_servers(&tool);
                 for server in servers {
@@ -757,20 +4684,31 @@ mod commands {
             imported.push(tool);
         }
         
+        finish_change("import_configs", pending, &changes);
         Ok(imported)
     }
 }
 
 // ===== App Setup =====
 
+#[cfg(feature = "gui")]
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_log::Builder::default().level(log::LevelFilter::Info).build())
+        .manage(InspectorSessions::default())
+        .setup(|app| {
+            start_backup_scheduler(app.handle().clone());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             commands::get_tools,
             commands::get_settings,
             commands::update_tool_path,
+            commands::update_backup_retention,
+            commands::update_backup_schedule,
+            commands::update_backup_destinations,
+            commands::update_snapshot_destinations,
             commands::add_custom_tool,
             commands::update_custom_tool,
             commands::delete_custom_tool,
@@ -780,13 +4718,450 @@ pub fn run() {
             commands::delete_server,
             commands::toggle_server,
             commands::sync_configs,
+            commands::set_transcript_recording,
+            commands::get_transcripts,
+            commands::get_transcript,
+            commands::replay_transcript,
+            commands::get_journal,
+            commands::undo,
+            commands::redo,
+            commands::get_history,
+            commands::get_server_history,
+            commands::checkout_history,
+            commands::check_server,
+            commands::get_health_history,
+            commands::get_inventory,
+            commands::estimate_token_budget,
+            commands::find_tool_collisions,
+            commands::check_commands,
+            commands::check_paths,
+            commands::doctor,
+            commands::open_inspector,
+            commands::inspector_call_tool,
+            commands::inspector_read_resource,
+            commands::inspector_get_prompt,
+            commands::close_inspector,
             commands::get_backups,
             commands::create_backup,
             commands::restore_backup,
+            commands::restore_servers,
+            commands::diff_backup,
+            commands::diff_backups,
             commands::delete_backup,
+            commands::verify_backups,
             commands::export_configs,
             commands::import_configs,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ===== Stdio Health Checks =====
+
+    // A stdio server written as a shell script. Requests from the client get ids 1, 2, ...
+    // in order, which is all the script needs to know to answer them.
+    #[cfg(unix)]
+    fn stub_server(script: &str) -> McpServer {
+        McpServer {
+            name: "stub".to_string(),
+            server_type: "stdio".to_string(),
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), script.to_string()],
+            env: HashMap::new(),
+            url: String::new(),
+            headers: HashMap::new(),
+            enabled: true,
+        }
+    }
+
+    #[cfg(unix)]
+    const STUB_SERVER: &str = r#"
+        echo "stub starting" >&2
+        read -r initialize
+        echo '{"jsonrpc":"2.0","id":1,"result":{"protocolVersion":"2025-06-18","capabilities":{"tools":{}},"serverInfo":{"name":"stub","version":"1.2.3"}}}'
+        read -r initialized
+        read -r list
+        echo '{"jsonrpc":"2.0","id":2,"result":{"tools":[{"name":"read","inputSchema":{"type":"object"}},{"name":"write","inputSchema":{"type":"object"}}]}}'
+        cat > /dev/null
+    "#;
+
+    #[cfg(unix)]
+    #[test]
+    fn health_check_reports_tools_of_a_working_server() {
+        let check = run_health_check(&stub_server(STUB_SERVER), std::time::Duration::from_secs(10));
+        assert_eq!(check.status, "ok", "{:?}", check.error);
+        assert_eq!(check.transport, "stdio");
+        assert_eq!(check.tool_count, Some(2));
+        assert_eq!(check.protocol_version.as_deref(), Some("2025-06-18"));
+        assert_eq!(check.server_info.as_ref().and_then(|i| i.get("version")).and_then(|v| v.as_str()), Some("1.2.3"));
+        assert!(check.startup_ms.is_some());
+        assert!(check.stderr.contains("stub starting"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn health_check_times_out_on_a_silent_server() {
+        let check = run_health_check(&stub_server("cat > /dev/null"), std::time::Duration::from_millis(300));
+        assert_eq!(check.status, "timeout");
+        assert_eq!(check.error_class.as_deref(), Some("timeout"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn health_check_reports_a_server_that_exits_with_its_stderr() {
+        let check = run_health_check(
+            &stub_server("echo 'missing API key' >&2; exit 3"),
+            std::time::Duration::from_secs(10),
+        );
+        assert_eq!(check.status, "error");
+        assert_eq!(check.error_class.as_deref(), Some("exited"));
+        assert!(check.stderr.contains("missing API key"), "{:?}", check.stderr);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn health_check_gives_up_on_a_server_that_closes_stdout_and_keeps_running() {
+        let started = std::time::Instant::now();
+        let check = run_health_check(&stub_server("exec >&-; exec sleep 30"), std::time::Duration::from_millis(500));
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        assert_eq!(check.status, "timeout");
+        assert!(check.error.as_deref().unwrap_or_default().contains("closed its output"), "{:?}", check.error);
+    }
+
+    #[test]
+    fn health_check_reports_a_missing_command() {
+        let server = McpServer {
+            name: "missing".to_string(),
+            server_type: "stdio".to_string(),
+            command: "mcp-manager-no-such-command".to_string(),
+            args: vec![],
+            env: HashMap::new(),
+            url: String::new(),
+            headers: HashMap::new(),
+            enabled: true,
+        };
+        let check = run_health_check(&server, std::time::Duration::from_secs(5));
+        assert_eq!(check.status, "error");
+        assert_eq!(check.error_class.as_deref(), Some("spawn"));
+    }
+
+    // ===== HTTP Health Checks =====
+
+    struct StubRequest {
+        method: String,
+        path: String,
+        // Header lines, lowercased
+        headers: Vec<String>,
+        body: String,
+    }
+
+    type StubHandler = dyn Fn(&StubRequest, &mut std::net::TcpStream) + Send + Sync;
+
+    // A stand-in HTTP server: every connection gets its own thread, and `respond` writes
+    // the whole response. Returns the server URL.
+    fn http_stub(respond: std::sync::Arc<StubHandler>) -> String {
+        use std::io::{BufRead, Read};
+        
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().filter_map(|s| s.ok()) {
+                let respond = respond.clone();
+                std::thread::spawn(move || {
+                    let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+                    let mut request_line = String::new();
+                    reader.read_line(&mut request_line).unwrap();
+                    let mut parts = request_line.split_whitespace();
+                    let method = parts.next().unwrap_or_default().to_string();
+                    let path = parts.next().unwrap_or_default().to_string();
+                    
+                    let mut headers = vec![];
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        if line.trim().is_empty() {
+                            break;
+                        }
+                        headers.push(line.trim().to_lowercase());
+                    }
+                    let length = headers.iter()
+                        .find_map(|h| h.strip_prefix("content-length:"))
+                        .and_then(|l| l.trim().parse().ok())
+                        .unwrap_or(0);
+                    let mut body = vec![0; length];
+                    reader.read_exact(&mut body).unwrap();
+                    
+                    let request = StubRequest { method, path, headers, body: String::from_utf8(body).unwrap() };
+                    respond(&request, &mut stream);
+                });
+            }
+        });
+        url
+    }
+
+    fn write_response(stream: &mut std::net::TcpStream, status: &str, content_type: &str, body: &str) {
+        use std::io::Write;
+        let _ = write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status, content_type, body.len(), body,
+        );
+    }
+
+    // What a minimal server answers, or `None` for notifications
+    fn stub_reply(body: &str) -> Option<serde_json::Value> {
+        let request: serde_json::Value = serde_json::from_str(body).unwrap();
+        let result = match request.get("method")?.as_str()? {
+            "initialize" => serde_json::json!({
+                "protocolVersion": MCP_PROTOCOL_VERSION,
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "http-stub", "version": "2.0.0" },
+            }),
+            "tools/list" => serde_json::json!({ "tools": [{ "name": "search", "inputSchema": { "type": "object" } }] }),
+            _ => serde_json::json!({}),
+        };
+        Some(serde_json::json!({ "jsonrpc": "2.0", "id": request.get("id")?, "result": result }))
+    }
+
+    fn http_server(url: &str) -> McpServer {
+        McpServer {
+            name: "remote".to_string(),
+            server_type: "http".to_string(),
+            command: String::new(),
+            args: vec![],
+            env: HashMap::new(),
+            url: url.to_string(),
+            headers: HashMap::new(),
+            enabled: true,
+        }
+    }
+
+    fn check_http(server: &McpServer) -> HealthCheck {
+        run_health_check(server, std::time::Duration::from_secs(10))
+    }
+
+    #[test]
+    fn health_check_over_streamable_http_with_json_responses() {
+        let url = http_stub(std::sync::Arc::new(|request: &StubRequest, stream: &mut std::net::TcpStream| {
+            match (request.method.as_str(), stub_reply(&request.body)) {
+                ("POST", Some(reply)) => write_response(stream, "200 OK", "application/json", &reply.to_string()),
+                _ => write_response(stream, "202 Accepted", "application/json", ""),
+            }
+        }));
+        let check = check_http(&http_server(&url));
+        assert_eq!(check.status, "ok", "{:?}", check.error);
+        assert_eq!(check.transport, "streamable-http");
+        assert_eq!(check.tool_count, Some(1));
+    }
+
+    #[test]
+    fn health_check_over_streamable_http_with_event_stream_responses() {
+        let url = http_stub(std::sync::Arc::new(|request: &StubRequest, stream: &mut std::net::TcpStream| {
+            match (request.method.as_str(), stub_reply(&request.body)) {
+                ("POST", Some(reply)) => {
+                    let events = format!("event: message\ndata: {}\n\n", reply);
+                    write_response(stream, "200 OK", "text/event-stream", &events);
+                }
+                _ => write_response(stream, "202 Accepted", "application/json", ""),
+            }
+        }));
+        let check = check_http(&http_server(&url));
+        assert_eq!(check.status, "ok", "{:?}", check.error);
+        assert_eq!(check.transport, "streamable-http");
+        assert_eq!(check.tool_count, Some(1));
+    }
+
+    #[test]
+    fn health_check_falls_back_to_legacy_sse() {
+        use std::io::Write;
+        
+        // Responses go out on the event stream opened by the GET, not in the POST responses
+        let events: std::sync::Arc<std::sync::Mutex<Option<std::net::TcpStream>>> = Default::default();
+        let (closed_sender, closed) = std::sync::mpsc::channel();
+        let url = http_stub(std::sync::Arc::new(move |request: &StubRequest, stream: &mut std::net::TcpStream| {
+            match (request.method.as_str(), request.path.as_str()) {
+                ("GET", "/mcp") => {
+                    let _ = write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\r\n");
+                    let _ = write!(stream, "event: endpoint\ndata: /messages?session=1\n\n");
+                    *events.lock().unwrap() = stream.try_clone().ok();
+                    // Keep the stream open until the client goes away
+                    let _ = std::io::copy(stream, &mut std::io::sink());
+                    let _ = closed_sender.send(());
+                }
+                ("POST", "/messages?session=1") => {
+                    if let (Some(reply), Some(events)) = (stub_reply(&request.body), events.lock().unwrap().as_mut()) {
+                        let _ = write!(events, "event: message\ndata: {}\n\n", reply);
+                    }
+                    write_response(stream, "202 Accepted", "text/plain", "");
+                }
+                _ => write_response(stream, "405 Method Not Allowed", "text/plain", ""),
+            }
+        }));
+        let check = check_http(&http_server(&url));
+        assert_eq!(check.status, "ok", "{:?}", check.error);
+        assert_eq!(check.transport, "sse");
+        assert_eq!(check.tool_count, Some(1));
+        assert!(closed.recv_timeout(std::time::Duration::from_secs(5)).is_ok(), "event stream left open");
+    }
+
+    #[test]
+    fn health_check_reports_rejected_credentials_as_unauthorized() {
+        for status in ["401 Unauthorized", "403 Forbidden"] {
+            let url = http_stub(std::sync::Arc::new(move |_: &StubRequest, stream: &mut std::net::TcpStream| {
+                write_response(stream, status, "application/json", "{}");
+            }));
+            let check = check_http(&http_server(&url));
+            assert_eq!(check.status, "unauthorized", "{}", status);
+            assert_eq!(check.http_status, status[..3].parse().ok());
+        }
+    }
+
+    #[test]
+    fn health_check_sends_configured_headers() {
+        let url = http_stub(std::sync::Arc::new(|request: &StubRequest, stream: &mut std::net::TcpStream| {
+            if !request.headers.iter().any(|h| h == "authorization: bearer s3cret") {
+                return write_response(stream, "401 Unauthorized", "application/json", "{}");
+            }
+            match stub_reply(&request.body) {
+                Some(reply) => write_response(stream, "200 OK", "application/json", &reply.to_string()),
+                None => write_response(stream, "202 Accepted", "application/json", ""),
+            }
+        }));
+        let mut server = http_server(&url);
+        assert_eq!(check_http(&server).status, "unauthorized");
+        
+        server.headers.insert("Authorization".to_string(), "Bearer s3cret".to_string());
+        let check = check_http(&server);
+        assert_eq!(check.status, "ok", "{:?}", check.error);
+    }
+
+    #[test]
+    fn headers_survive_a_config_round_trip() {
+        let dir = std::env::temp_dir().join(format!("mcp-manager-headers-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut server = http_server("https://example.com/mcp");
+        server.headers.insert("Authorization".to_string(), "Bearer s3cret".to_string());
+        
+        let json = dir.join("mcp.json");
+        write_json_servers(&json, "mcpServers", &[server.clone()]).unwrap();
+        assert_eq!(read_json_servers(&json, "mcpServers")[0].headers, server.headers);
+        
+        let toml = dir.join("config.toml");
+        write_toml_servers(&toml, &[server.clone()]).unwrap();
+        assert_eq!(read_toml_servers(&toml)[0].headers, server.headers);
+        
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn header_changes_show_up_in_diffs() {
+        let before = http_server("https://example.com/mcp");
+        let mut after = before.clone();
+        after.headers.insert("Authorization".to_string(), "Bearer s3cret".to_string());
+        
+        let diff = diff_servers(Some(&before), Some(&after));
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff[0].field, "headers.Authorization");
+        assert_eq!(diff[0].after, serde_json::json!("Bearer s3cret"));
+    }
+
+    // ===== Doctor =====
+
+    #[test]
+    fn env_placeholders_must_fill_the_whole_value() {
+        for placeholder in ["<your-api-key>", "YOUR_API_KEY_HERE", "your-token", "xxxx-xxxx-xxxx", "changeme", "TODO"] {
+            assert_eq!(env_problem(placeholder).map(|(severity, _)| severity), Some("error"), "{}", placeholder);
+        }
+        for value in ["sk-abc<def>", "a1b2xxxx9z", "mytodolist", "Ask your admin", "<", "$ecretPass1", "$lowercase"] {
+            assert_eq!(env_problem(value), None, "{}", value);
+        }
+        for reference in ["$MCP_MANAGER_TEST_UNSET", "${MCP_MANAGER_TEST_UNSET}", "${env:MCP_MANAGER_TEST_UNSET}"] {
+            assert_eq!(env_problem(reference).map(|(severity, _)| severity), Some("warning"), "{}", reference);
+        }
+    }
+
+    // ===== Path Checks =====
+
+    // Runs `f` with HOME pointing at a fresh directory. HOME is process-wide, so tests
+    // that change it take turns.
+    #[cfg(unix)]
+    fn with_temp_home(name: &str, f: impl FnOnce(&Path)) {
+        static HOME_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+        let _lock = HOME_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        
+        let home = std::env::temp_dir().join(format!("mcp-manager-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&home);
+        fs::create_dir_all(&home).unwrap();
+        let home = fs::canonicalize(&home).unwrap();
+        let previous = std::env::var_os("HOME");
+        std::env::set_var("HOME", &home);
+        
+        f(&home);
+        
+        match previous {
+            Some(value) => std::env::set_var("HOME", value),
+            None => std::env::remove_var("HOME"),
+        }
+        let _ = fs::remove_dir_all(&home);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn backup_ids_must_name_a_file_inside_a_backup_directory() {
+        with_temp_home("backup-ids", |home| {
+            let backup_dir = get_backup_dir();
+            fs::create_dir_all(&backup_dir).unwrap();
+            fs::write(backup_dir.join("backup-2025-01-01T00-00-00.json"), "{}").unwrap();
+            fs::write(backup_dir.join(".hidden.json"), "{}").unwrap();
+            fs::write(home.join("outside.json"), "{}").unwrap();
+            std::os::unix::fs::symlink(home.join("outside.json"), backup_dir.join("linked.json")).unwrap();
+            
+            assert!(BackupId::parse("backup-2025-01-01T00-00-00.json").is_ok());
+            assert!(BackupId::parse("../outside.json").is_err());
+            assert!(BackupId::parse(&home.join("outside.json").to_string_lossy()).is_err());
+            assert!(BackupId::parse(".hidden.json").is_err());
+            assert!(BackupId::parse("linked.json").is_err());
+            assert!(BackupId::parse("missing.json").is_err());
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn config_paths_must_stay_out_of_the_data_directory() {
+        with_temp_home("config-paths", |home| {
+            let data_dir = home.join(".mcp-manager");
+            fs::create_dir_all(&data_dir).unwrap();
+            std::os::unix::fs::symlink(&data_dir, home.join("linked")).unwrap();
+            
+            assert_eq!(validate_config_path("~/.claude.json").unwrap(), home.join(".claude.json"));
+            assert!(validate_config_path("relative/mcp.json").is_err());
+            assert!(validate_config_path("~/../mcp.json").is_err());
+            assert!(validate_config_path("~").is_err());
+            assert!(validate_config_path("~/.mcp-manager/settings.json").is_err());
+            assert!(validate_config_path(&data_dir.join("backups/mcp.json").to_string_lossy()).is_err());
+            assert!(validate_config_path("~/linked/settings.json").is_err());
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn transcript_names_must_be_plain_files() {
+        with_temp_home("transcripts", |home| {
+            let dir = get_transcripts_dir("Claude Code", "stub");
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("2025-01-01.jsonl"), "").unwrap();
+            fs::write(dir.join(".hidden.jsonl"), "").unwrap();
+            fs::write(home.join("outside.jsonl"), "").unwrap();
+            
+            assert!(transcript_path("Claude Code", "stub", "2025-01-01.jsonl").is_ok());
+            assert!(transcript_path("Claude Code", "stub", "../../../../outside.jsonl").is_err());
+            assert!(transcript_path("Claude Code", "stub", &home.join("outside.jsonl").to_string_lossy()).is_err());
+            assert!(transcript_path("Claude Code", "stub", ".hidden.jsonl").is_err());
+            assert!(transcript_path("Claude Code", "stub", "missing.jsonl").is_err());
+        });
+    }
+}
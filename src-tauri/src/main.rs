@@ -2,5 +2,11 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+  // AI tools start the app this way when a server's transcript is being recorded
+  let args: Vec<String> = std::env::args().skip(1).collect();
+  if args.first().map(|a| a.as_str()) == Some(app_lib::RECORD_FLAG) {
+    std::process::exit(app_lib::run_recording_shim(&args[1..]));
+  }
+
   app_lib::run();
 }
@@ -367,6 +367,10 @@ function getServerModalHtml(server = null, tool = null) {
           <input type="text" class="form-input" id="server-url" 
                  value="${server?.url || ''}" placeholder="http://localhost:3001/sse">
         </div>
+        <div class="form-group">
+          <label class="form-label">Headers (Name: value, one per line)</label>
+          <textarea class="form-textarea" id="server-headers" placeholder="Authorization: Bearer xxx">${Object.entries(server?.headers || {}).map(([k, v]) => `${k}: ${v}`).join('\n')}</textarea>
+        </div>
       </div>
       
       <div class="form-group">
@@ -428,6 +432,7 @@ function setupServerModalHandlers() {
       document.getElementById('server-command').value = tmpl.command || '';
       document.getElementById('server-args').value = (tmpl.args || []).join('\n');
       document.getElementById('server-url').value = tmpl.url || '';
+      document.getElementById('server-headers').value = Object.entries(tmpl.headers || {}).map(([k, v]) => `${k}: ${v}`).join('\n');
       document.getElementById('server-env').value = Object.entries(tmpl.env || {}).map(([k, v]) => `${k}=${v} `).join('\n');
 
       const isStdio = tmpl.type === 'stdio';
@@ -455,6 +460,13 @@ window.saveServer = async function (isEdit) {
     server.args = document.getElementById('server-args').value.split('\n').map(s => s.trim()).filter(Boolean);
   } else {
     server.url = document.getElementById('server-url').value.trim();
+    server.headers = {};
+    document.getElementById('server-headers').value.split('\n').forEach(line => {
+      const idx = line.indexOf(':');
+      if (idx > 0) {
+        server.headers[line.slice(0, idx).trim()] = line.slice(idx + 1).trim();
+      }
+    });
   }
 
   // Parse env vars
@@ -549,6 +561,9 @@ window.copyServerJson = function (tool, name) {
     }
   } else {
     serverConfig.url = server.url;
+    if (server.headers && Object.keys(server.headers).length) {
+      serverConfig.headers = server.headers;
+    }
   }
 
   if (server.env && Object.keys(server.env).length) {
@@ -748,8 +763,8 @@ async function openBackupModal() {
         return `
             <div class="backup-item">
               <div class="backup-info">
-                <div class="backup-date">${displayDate}</div>
-                <div class="backup-file">${escapeHtml(b.name)}</div>
+                <div class="backup-date">${displayDate}${b.label ? ` · ${escapeHtml(b.label)}` : ''}</div>
+                <div class="backup-file" title="${escapeHtml((b.origins || []).join('\n'))}">${escapeHtml(b.name)}${b.trigger && b.trigger !== 'manual' ? ` (${escapeHtml(b.trigger)})` : ''}</div>
               </div>
               <div style="display: flex; gap: 0.5rem;" class="backup-actions">
                 <button class="btn btn-secondary btn-sm" data-action="restore-backup" data-filename="${escapeHtml(b.name)}">Restore</button>
@@ -778,7 +793,11 @@ window.restoreBackup = async function (filename) {
   if (!confirm('Restore from this backup? Current configs will be overwritten.')) return;
 
   try {
-    await api.restoreBackup(filename);
+    const backup = (await api.getBackups()).find(b => b.name === filename);
+    const passphrase = backup?.encrypted ? prompt('This backup is encrypted. Enter its passphrase:') : null;
+    if (backup?.encrypted && !passphrase) return;
+
+    await api.restoreBackup(filename, null, null, passphrase);
     await loadConfigs();
     closeModal();
     showToast('Backup restored', 'success');
@@ -860,8 +879,10 @@ window.performImport = async function () {
   try {
     const text = await fileInput.files[0].text();
     const data = JSON.parse(text);
+    const passphrase = data.ciphertext ? prompt('This export is encrypted. Enter its passphrase:') : null;
+    if (data.ciphertext && !passphrase) return;
 
-    await api.importConfigs(data, merge);
+    await api.importConfigs(data, merge, passphrase);
     await loadConfigs();
     closeModal();
     showToast('Configs imported', 'success');
@@ -946,6 +967,7 @@ window.performPasteJson = async function () {
         args: config.args || [],
         env: config.env || {},
         url: config.url || '',
+        headers: config.headers || {},
         enabled: config.disabled !== true
       };
 
@@ -1322,6 +1344,10 @@ async function init() {
 
   setupServerListHandlers();
 
+  api.onScheduledBackup(({ backup }) => {
+    if (backup) showToast(`Scheduled backup created: ${backup.name}`, 'info');
+  });
+
   // Load data
   await loadTemplates();
   await loadTools();
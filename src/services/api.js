@@ -1,4 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 
 // ===== Tools =====
 export async function getTools() {
@@ -30,29 +31,146 @@ export async function syncConfigs(from, to, serverNames = null) {
     return await invoke('sync_configs', { from, to, serverNames });
 }
 
+// ===== Health Checks =====
+export async function checkServer(tool, server, timeoutMs = null) {
+    return await invoke('check_server', { tool, server, timeoutMs });
+}
+
+export async function getHealthHistory(tool, serverName, limit = null) {
+    return await invoke('get_health_history', { tool, serverName, limit });
+}
+
+export async function getInventory(tool, serverName = null, refresh = false) {
+    return await invoke('get_inventory', { tool, serverName, refresh });
+}
+
+export async function estimateTokenBudget(tool = null, refresh = false) {
+    return await invoke('estimate_token_budget', { tool, refresh });
+}
+
+export async function findToolCollisions(tool = null, refresh = false) {
+    return await invoke('find_tool_collisions', { tool, refresh });
+}
+
+export async function checkCommands() {
+    return await invoke('check_commands');
+}
+
+export async function checkPaths() {
+    return await invoke('check_paths');
+}
+
+export async function doctor() {
+    return await invoke('doctor');
+}
+
+// ===== Transcripts =====
+export async function setTranscriptRecording(tool, serverName, enabled) {
+    return await invoke('set_transcript_recording', { tool, serverName, enabled });
+}
+
+export async function getTranscripts(tool, serverName) {
+    return await invoke('get_transcripts', { tool, serverName });
+}
+
+export async function getTranscript(tool, serverName, name) {
+    return await invoke('get_transcript', { tool, serverName, name });
+}
+
+export async function replayTranscript(tool, serverName, name) {
+    return await invoke('replay_transcript', { tool, serverName, name });
+}
+
+// ===== Inspector =====
+export async function openInspector(tool, serverName) {
+    return await invoke('open_inspector', { tool, serverName });
+}
+
+export async function inspectorCallTool(sessionId, name, args = {}, timeoutMs = null) {
+    return await invoke('inspector_call_tool', { sessionId, name, arguments: args, timeoutMs });
+}
+
+export async function inspectorReadResource(sessionId, uri, timeoutMs = null) {
+    return await invoke('inspector_read_resource', { sessionId, uri, timeoutMs });
+}
+
+export async function inspectorGetPrompt(sessionId, name, args = {}, timeoutMs = null) {
+    return await invoke('inspector_get_prompt', { sessionId, name, arguments: args, timeoutMs });
+}
+
+export async function closeInspector(sessionId) {
+    return await invoke('close_inspector', { sessionId });
+}
+
 // ===== Backup =====
 export async function getBackups() {
     return await invoke('get_backups');
 }
 
-export async function createBackup() {
-    return await invoke('create_backup');
+export async function createBackup(label = null, note = null, passphrase = null) {
+    return await invoke('create_backup', { label, note, passphrase });
+}
+
+export async function restoreBackup(filename, toolsToRestore = null, mode = null, passphrase = null) {
+    return await invoke('restore_backup', { filename, toolsToRestore, mode, passphrase });
+}
+
+export async function restoreServers(filename, servers, passphrase = null) {
+    return await invoke('restore_servers', { filename, servers, passphrase });
+}
+
+export async function diffBackup(filename, passphrase = null) {
+    return await invoke('diff_backup', { filename, passphrase });
 }
 
-export async function restoreBackup(filename, toolsToRestore = null) {
-    return await invoke('restore_backup', { filename, toolsToRestore });
+export async function diffBackups(from, to, passphrase = null) {
+    return await invoke('diff_backups', { from, to, passphrase });
 }
 
 export async function deleteBackup(filename) {
     return await invoke('delete_backup', { filename });
 }
 
+export async function verifyBackups() {
+    return await invoke('verify_backups');
+}
+
+// ===== Undo/Redo =====
+export async function getJournal() {
+    return await invoke('get_journal');
+}
+
+export async function undo(force = false) {
+    return await invoke('undo', { force });
+}
+
+export async function redo(force = false) {
+    return await invoke('redo', { force });
+}
+
+// ===== History =====
+export async function getHistory(limit = null) {
+    return await invoke('get_history', { limit });
+}
+
+export async function getServerHistory(tool, serverName, limit = null) {
+    return await invoke('get_server_history', { tool, serverName, limit });
+}
+
+export async function checkoutHistory(commit, tools = null) {
+    return await invoke('checkout_history', { commit, tools });
+}
+
 // ===== Import/Export =====
-export async function exportConfigs() {
-    return await invoke('export_configs');
+export async function exportConfigs(passphrase = null) {
+    return await invoke('export_configs', { passphrase });
 }
 
-export async function importConfigs(data, merge = false) {
+// Encrypted exports carry a `ciphertext` instead of a `tools` map
+export async function importConfigs(data, merge = false, passphrase = null) {
+    if (data.ciphertext) {
+        return await invoke('import_configs', { bundle: data, passphrase, merge });
+    }
     return await invoke('import_configs', { tools: data.tools, merge });
 }
 
@@ -61,6 +179,26 @@ export async function updateToolPath(tool, path) {
     return await invoke('update_tool_path', { tool, path });
 }
 
+export async function updateBackupRetention(policy) {
+    return await invoke('update_backup_retention', { policy });
+}
+
+export async function updateBackupSchedule(schedule) {
+    return await invoke('update_backup_schedule', { schedule });
+}
+
+export async function updateBackupDestinations(destinations) {
+    return await invoke('update_backup_destinations', { destinations });
+}
+
+export async function updateSnapshotDestinations(enabled) {
+    return await invoke('update_snapshot_destinations', { enabled });
+}
+
+export async function onScheduledBackup(callback) {
+    return await listen('scheduled-backup', event => callback(event.payload));
+}
+
 // ===== Custom Tools =====
 export async function addCustomTool(tool) {
     return await invoke('add_custom_tool', { tool });